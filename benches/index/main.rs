@@ -0,0 +1,205 @@
+//! Compares memory and query latency of `TermIndex` against the byte trie it replaced.
+//!
+//! Run with `cargo bench --bench index`. Terms are generated unless `MYDICT_BENCH_WORDS`
+//! is set to a file of one term per line, e.g. `/usr/share/dict/words`.
+
+#![allow(clippy::cast_precision_loss)]
+
+mod trie;
+
+use mydict::TermIndex;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeSet;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use trie::Trie;
+
+/// Number of generated terms, which is about the size of a large dictionary.
+const TERMS: usize = 500_000;
+const MIB: f64 = 1_048_576.0;
+
+/// Counts bytes currently allocated.
+struct Counter;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counter {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+		unsafe { System.alloc(layout) }
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+		unsafe { System.dealloc(ptr, layout) }
+	}
+}
+
+#[global_allocator]
+static GLOBAL: Counter = Counter;
+
+/// A xorshift generator, so that every run uses the same terms.
+struct Rng(u64);
+
+impl Rng {
+	fn below(&mut self, n: usize) -> usize {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		usize::try_from(self.0 % n as u64).unwrap_or_default()
+	}
+}
+
+/// Generate word-like terms, which share prefixes and suffixes like real ones do.
+fn generate_terms(rng: &mut Rng) -> Vec<String> {
+	const SYLLABLES: [&str; 40] = [
+		"a", "ab", "ac", "al", "an", "ar", "be", "bi", "ca", "co", "con", "de", "di", "dis", "e",
+		"en", "ex", "fa", "in", "la", "le", "li", "ma", "mi", "mo", "na", "ne", "o", "pa", "per",
+		"pre", "pro", "ra", "re", "ri", "sa", "se", "ta", "ti", "un",
+	];
+	const SUFFIXES: [&str; 10] = [
+		"", "s", "ed", "ing", "tion", "ly", "ness", "able", "ment", "er",
+	];
+
+	let mut terms = BTreeSet::new();
+	while terms.len() < TERMS {
+		let mut term = String::new();
+		for _ in 0..=rng.below(5) {
+			term.push_str(SYLLABLES[rng.below(SYLLABLES.len())]);
+		}
+		term.push_str(SUFFIXES[rng.below(SUFFIXES.len())]);
+		terms.insert(term);
+	}
+
+	terms.into_iter().collect()
+}
+
+fn load_terms(rng: &mut Rng) -> Vec<String> {
+	let Ok(path) = std::env::var("MYDICT_BENCH_WORDS") else {
+		return generate_terms(rng);
+	};
+
+	let mut terms: Vec<String> = std::fs::read_to_string(&path)
+		.unwrap_or_else(|err| panic!("failed to read {path}: {err}"))
+		.lines()
+		.map(str::to_string)
+		.collect();
+	terms.sort();
+	terms.dedup();
+
+	terms
+}
+
+/// Returns the result and how long `f` takes, and how many bytes it leaves allocated.
+fn measure<R>(f: impl FnOnce() -> R) -> (R, f64, usize) {
+	let before = ALLOCATED.load(Ordering::Relaxed);
+	let t0 = Instant::now();
+	let result = f();
+	let secs = t0.elapsed().as_secs_f64();
+	let bytes = ALLOCATED.load(Ordering::Relaxed).saturating_sub(before);
+
+	(result, secs, bytes)
+}
+
+/// Print the average latency of running `f` on each query.
+fn bench<Q>(name: &str, queries: &[Q], mut f: impl FnMut(&Q) -> usize) {
+	let t0 = Instant::now();
+	let mut results = 0;
+	for query in queries {
+		results += black_box(f(query));
+	}
+	let micros = t0.elapsed().as_secs_f64() * 1e6 / queries.len() as f64;
+	println!("{name:<32} {micros:>12.2} us/query {results:>12} results");
+}
+
+fn main() {
+	let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+	let terms = load_terms(&mut rng);
+	println!(
+		"{} terms, {} bytes\n",
+		terms.len(),
+		terms.iter().map(String::len).sum::<usize>()
+	);
+
+	let (trie, secs, bytes) = measure(|| {
+		let mut trie = Trie::new();
+		for term in &terms {
+			trie.insert(term);
+		}
+		trie
+	});
+	println!(
+		"{:<32} {secs:>12.3} s {:>12.1} MiB",
+		"build Trie",
+		bytes as f64 / MIB
+	);
+
+	let (index, secs, bytes) = measure(|| TermIndex::from_sorted(&terms));
+	println!(
+		"{:<32} {secs:>12.3} s {:>12.1} MiB",
+		"build TermIndex",
+		bytes as f64 / MIB
+	);
+	println!(
+		"{:<32} {:>27.1} MiB\n",
+		"TermIndex heap size",
+		index.heap_size() as f64 / MIB
+	);
+
+	let pick = |rng: &mut Rng, n: usize, max_len: usize| -> Vec<String> {
+		(0..n)
+			.map(|_| {
+				let term = &terms[rng.below(terms.len())];
+				term.chars().take(1 + rng.below(max_len)).collect()
+			})
+			.collect()
+	};
+	let short_prefixes = pick(&mut rng, 100, 2);
+	let long_prefixes = pick(&mut rng, 10_000, 8);
+	let exact: Vec<String> = (0..100_000)
+		.map(|_| terms[rng.below(terms.len())].clone())
+		.collect();
+	let typos: Vec<String> = (0..100)
+		.map(|_| {
+			let mut term: Vec<char> = terms[rng.below(terms.len())].chars().collect();
+			let i = rng.below(term.len());
+			term[i] = 'x';
+			term.into_iter().collect()
+		})
+		.collect();
+
+	bench("Trie prefix (1-2 chars)", &short_prefixes, |p| {
+		trie.search(p).len()
+	});
+	bench("TermIndex prefix (1-2 chars)", &short_prefixes, |p| {
+		index.prefix(p).count()
+	});
+	bench("TermIndex prefix 100 (1-2 chars)", &short_prefixes, |p| {
+		index.prefix(p).take(100).count()
+	});
+	bench("Trie prefix (1-8 chars)", &long_prefixes, |p| {
+		trie.search(p).len()
+	});
+	bench("TermIndex prefix (1-8 chars)", &long_prefixes, |p| {
+		index.prefix(p).count()
+	});
+	bench("TermIndex exact", &exact, |term| {
+		usize::from(index.contains(term))
+	});
+	bench("TermIndex ordinal lookup", &exact, |term| {
+		index
+			.ordinal(term)
+			.and_then(|ordinal| index.get(ordinal))
+			.map_or(0, |_| 1)
+	});
+	bench("TermIndex range", &long_prefixes, |p| {
+		index.range(Some(p), None).take(100).count()
+	});
+	bench("Trie suggest (distance 2)", &typos, |term| {
+		trie.suggest(term, 2, 10).len()
+	});
+	bench("TermIndex suggest (distance 2)", &typos, |term| {
+		index.suggest(term, 2, 10).len()
+	});
+}
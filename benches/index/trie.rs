@@ -0,0 +1,156 @@
+//! The byte trie used before `TermIndex`, kept as the baseline of benchmarks.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+pub struct Trie {
+	map: BTreeMap<u8, Trie>,
+	is_end: bool,
+}
+
+impl Trie {
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			map: BTreeMap::new(),
+			is_end: false,
+		}
+	}
+
+	pub fn insert(&mut self, s: &str) {
+		let mut current = self;
+		for byte in s.as_bytes() {
+			current = current.map.entry(*byte).or_default();
+		}
+		current.is_end = true;
+	}
+
+	fn lexicon_iter(&self, buffer: &mut Vec<u8>) -> Vec<String> {
+		let mut result = Vec::new();
+		if self.is_end {
+			result.push(String::from_utf8(buffer.clone()).unwrap());
+		}
+		for (byte, next) in &self.map {
+			buffer.push(*byte);
+			result.extend(next.lexicon_iter(buffer));
+			buffer.pop();
+		}
+
+		result
+	}
+
+	#[must_use]
+	pub fn search(&self, s: &str) -> Vec<String> {
+		let mut current = self;
+		let mut buffer = Vec::new();
+		for byte in s.as_bytes() {
+			if !current.map.contains_key(byte) {
+				return Vec::new();
+			}
+
+			buffer.push(*byte);
+			current = &current.map[byte];
+		}
+
+		current.lexicon_iter(&mut buffer)
+	}
+
+	/// Returns at most `limit` terms whose edit distance to `s` is not greater than
+	/// `max_distance`, ranked by distance and then lexicographically.
+	///
+	/// The distance is the optimal string alignment distance over chars, i.e. the
+	/// Levenshtein distance where swapping two adjacent chars counts as one edit.
+	#[must_use]
+	pub fn suggest(&self, s: &str, max_distance: usize, limit: usize) -> Vec<(String, usize)> {
+		let target: Vec<char> = s.chars().collect();
+		let row: Vec<usize> = (0..=target.len()).collect();
+		let mut state = SuggestState {
+			target: &target,
+			max_distance,
+			buffer: Vec::new(),
+			result: Vec::new(),
+		};
+		self.suggest_iter(&mut state, 0, &row, None);
+
+		state
+			.result
+			.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+		state.result.truncate(limit);
+		state.result
+	}
+
+	/// Walks the trie while computing the distance matrix one char at a time.
+	///
+	/// `char_start` is where the current (maybe incomplete) UTF-8 char begins in the
+	/// buffer, `row` is the matrix row of the last complete char and `prev` holds the
+	/// row and char before it, which transpositions need.
+	fn suggest_iter(
+		&self,
+		state: &mut SuggestState,
+		char_start: usize,
+		row: &[usize],
+		prev: Option<(&[usize], char)>,
+	) {
+		for (byte, next) in &self.map {
+			state.buffer.push(*byte);
+
+			let Ok(c) = std::str::from_utf8(&state.buffer[char_start..]) else {
+				// not a complete char yet, keep walking with the same rows
+				next.suggest_iter(state, char_start, row, prev);
+				state.buffer.pop();
+				continue;
+			};
+			let c = c.chars().next().unwrap_or_default();
+
+			let target = state.target;
+			let mut current = Vec::with_capacity(row.len());
+			current.push(row[0] + 1);
+			for j in 1..row.len() {
+				let cost = usize::from(target[j - 1] != c);
+				let mut distance = (row[j] + 1).min(current[j - 1] + 1).min(row[j - 1] + cost);
+				if let Some((prev_row, prev_char)) = prev
+					&& j > 1 && target[j - 1] == prev_char
+					&& target[j - 2] == c
+				{
+					distance = distance.min(prev_row[j - 2] + 1);
+				}
+				current.push(distance);
+			}
+
+			let distance = current[current.len() - 1];
+			if next.is_end && distance <= state.max_distance {
+				state.result.push((
+					String::from_utf8_lossy(&state.buffer).into_owned(),
+					distance,
+				));
+			}
+
+			// a transposition in the next row can still lower the distance by one
+			let lower_bound = current
+				.iter()
+				.min()
+				.copied()
+				.unwrap_or_default()
+				.min(row.iter().min().copied().unwrap_or_default() + 1);
+			if lower_bound <= state.max_distance {
+				let char_end = state.buffer.len();
+				next.suggest_iter(state, char_end, &current, Some((row, c)));
+			}
+
+			state.buffer.pop();
+		}
+	}
+}
+
+struct SuggestState<'a> {
+	target: &'a [char],
+	max_distance: usize,
+	buffer: Vec<u8>,
+	result: Vec<(String, usize)>,
+}
+
+impl Default for Trie {
+	fn default() -> Self {
+		Self::new()
+	}
+}
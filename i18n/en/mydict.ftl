@@ -3,5 +3,90 @@ about = About
 view = View
 file = File
 import = Import
+import-folder = Import Folder
+importing = Importing {$url}, please wait.
+importing-items = Importing {$count} items, please wait.
+choose-files = Choose files
+choose-folders = Choose folders
+dictionary-files = Dictionary files and archives
+import-report = Imported {$imported} of {$total} dictionaries
+import-unchanged = already imported
+import-skipped = skipped
+import-conflict = A different dictionary named "{$name}" is imported before
+replace = Replace
+keep-both = Keep both
+skip = Skip
+close = Close
+load-error = Failed to load {$name}: {$error}
 welcome = Welcome to My Dictionary! ✨
 git-description = Git commit {$hash} on {$date}
+did-you-mean = Did you mean:
+no-dictionary = No dictionary found, please import one
+not-found = Search not found
+prefix = Prefix
+wildcard = Wildcard
+regex = Regex
+all-dictionaries = All dictionaries
+accent-sensitive = Distinguish accents
+dictionaries = Dictionaries
+dictionary-name = Dictionary name
+dictionary-info = Dictionary Information
+info-name = Name
+info-path = Path
+info-format-version = Format version
+info-file-size = File size
+info-compression = Compression
+info-entries = Entries
+info-references = References
+info-senses = Senses
+info-definitions = Definitions
+info-examples = Examples
+info-parts-of-speech = Parts of speech
+info-languages = Languages
+info-index-cached = Index read from cache in
+info-index-built = Index built in
+loading = Loading...
+delete-dictionary = Delete dictionary
+delete-dictionary-body = The dictionary "{$name}" will be deleted from disk.
+cancel = Cancel
+go = Go
+back = Back
+forward = Forward
+history = History
+clear-history = Clear
+export-history = Export
+word-lists = Word Lists
+new-word-list = New list name
+create = Create
+delete = Delete
+export-csv = Export CSV
+export-markdown = Export Markdown
+export = Export
+invalid-url = {$url} is not a local file
+log = Log
+clear-log = Clear
+details = Details
+load-failed = Failed to load "{$name}", import it again to retry
+import-failed = Failed to import dictionaries
+import-files-failed = Failed to import {$count ->
+    [one] a dictionary
+   *[other] {$count} dictionaries
+}
+exported = Exported to {$path}
+export-failed = Failed to export
+dict-not-compatible = "{$name}" uses ODict {$version}, but at least {$required} is required, convert it again with a newer ODict
+rename-failed = Failed to rename the dictionary
+dict-name-empty = The dictionary name must not be empty
+dict-name-taken = Another dictionary is already named "{$name}"
+delete-failed = Failed to delete the dictionary
+dict-info-failed = Failed to read dictionary information
+dictionary-folders = Dictionary folders
+add-folder = Add folder
+add-folder-failed = Failed to add folders
+not-local-folder = {$url} is not a local folder
+save-config-failed = Failed to save settings, changes are kept until the app quits
+save-history-failed = Failed to save history, new lookups are kept until the app quits
+save-word-lists-failed = Failed to save word lists, changes are kept until the app quits
+data-backed-up = {$file} could not be read and is kept as {$backup}
+load-data-failed = Failed to read {$file}, changes are not saved to it until the app restarts
+unknown-window = Unknown window ID
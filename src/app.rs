@@ -1,36 +1,60 @@
 // SPDX-License-Identifier: MIT
 
+pub mod config;
+pub mod font;
+pub mod notice;
 pub mod tasks;
-pub mod utils;
 
 pub use tasks::*;
-pub use utils::*;
 
-use crate::config::Config;
-use crate::{Dictionary, fl};
-use crate::{LazyDict, elapsed_secs, now};
+use crate::SearchMode;
+use crate::history::{self, HistoryItem};
+use crate::link::LookupLink;
+use crate::remove_pending_files;
+use crate::render::{Format, render_entry};
+use crate::wordlist::{WordListItem, WordLists};
+use crate::{ArchiveKind, ConflictResolution, DictFileInfo, DictStore, ImportReport, ImportStatus};
+use crate::{DictInfo, Dictionary, Error, Folding, IndexCache, InfoField, SearchCursor, fl};
+use crate::{LazyDict, MINIMAL_ODICT_VERSION, elapsed_secs, format_size, format_timestamp, now};
+use config::Config;
 use cosmic::app::context_drawer;
 use cosmic::cosmic_config::{self, CosmicConfigEntry};
 use cosmic::dialog::file_chooser::{self, FileFilter};
 use cosmic::iced::Length::{self};
+use cosmic::iced::keyboard::{Key, Modifiers, key::Named};
 use cosmic::iced::widget::rule;
-use cosmic::iced::{Alignment, Subscription, window};
+use cosmic::iced::{Alignment, Event, Subscription, event, keyboard, window};
 use cosmic::prelude::*;
+use cosmic::widget::menu::key_bind::{KeyBind, Modifier};
+use cosmic::widget::toaster::{Toast, ToastId, Toasts};
 use cosmic::widget::{self, button, markdown, menu, nav_bar, scrollable, text};
 use cosmic::{
 	cosmic_theme::{self},
 	theme,
 };
-use directories::ProjectDirs;
 
-use odict::{DefinitionType, Entry, semver::SemanticVersion};
-use std::collections::HashMap;
-use std::path::PathBuf;
-use tracing::{debug, debug_span, error, info, info_span};
+use notice::{LOG_LIMIT, Notice};
+use odict::{Entry, semver::SemanticVersion};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{error, info, info_span};
 use url::Url;
 
 const REPOSITORY: &str = env!("CARGO_PKG_REPOSITORY");
 const APP_ICON: &[u8] = include_bytes!("../resources/icons/hicolor/scalable/apps/icon.svg");
+/// Maximum number of "did you mean" suggestions shown on a miss.
+const SUGGESTION_LIMIT: usize = 10;
+/// Number of search results loaded into the nav bar at a time.
+const RESULTS_PAGE_SIZE: usize = 100;
+/// Maximum number of terms matching a wildcard or regex pattern.
+const PATTERN_LIMIT: usize = 1000;
+/// Search terms starting with this are searched in definitions, examples and notes.
+const FULL_TEXT_PREFIX: &str = "def:";
+/// Maximum number of results of a full-text search.
+const FULL_TEXT_LIMIT: usize = 100;
 
 /// The application model stores app-specific state used to describe its interface and
 /// drive its logic.
@@ -45,11 +69,64 @@ pub struct AppModel {
 	key_binds: HashMap<menu::KeyBind, MenuAction>,
 	// Configuration data that persists between application runs.
 	config: Config,
-	config_manager: cosmic_config::Config,
+	/// Where the config is saved, `None` if it could not be opened.
+	config_manager: Option<cosmic_config::Config>,
 	dicts: Vec<LazyDict>,
-	dict_entry: Option<Entry>,
-	md_items: Vec<markdown::Item>,
-	selected_dict_url: Option<Url>,
+	/// Why dictionaries failed to load, they are not loaded again until imported again.
+	dict_errors: HashMap<PathBuf, Error>,
+	/// Entries of the selected term, one per dictionary.
+	sections: Vec<TermSection>,
+	/// Terms close to the search term, used when the search term is not found.
+	suggestions: Vec<String>,
+	/// Where to continue when more search results are needed.
+	results: SearchCursor,
+	/// Generation of the latest search, shared with search tasks.
+	search_generation: Arc<AtomicU64>,
+	/// Why the search term is not valid, e.g. a bad regex.
+	search_error: Option<String>,
+	/// Labels of `SearchMode::ALL`.
+	search_modes: Vec<String>,
+	/// Files and directories being imported.
+	importing: Vec<Url>,
+	/// Outcomes of the last import, shown until it's dismissed.
+	import_reports: Vec<ImportReport>,
+	/// Lookup history, oldest first.
+	history: Vec<HistoryItem>,
+	/// Index of the current item in `history`, used for back/forward navigation.
+	history_position: usize,
+	word_lists: WordLists,
+	/// Data files which could not be read, so they are not overwritten by saving.
+	readonly_paths: HashSet<PathBuf>,
+	/// Index of the word list shown in the word lists page, starred entries go there.
+	selected_list: usize,
+	/// Name input for creating a word list.
+	new_list_name: String,
+	/// The dictionary being renamed in the dictionary manager, with its new name.
+	renaming_dict: Option<(PathBuf, String)>,
+	/// The dictionary to delete once it's confirmed.
+	deleting_dict: Option<PathBuf>,
+	/// Shown in the dictionary information page, `None` while it's loading.
+	dict_info: Option<DictInfo>,
+	store: DictStore,
+	toasts: Toasts<Message>,
+	/// Notices shown before, oldest first, which are listed in the log page.
+	notices: Vec<Notice>,
+	/// Id of the notice whose details are shown in the log page.
+	expanded_notice: Option<u64>,
+	/// Notices of helpers which don't return tasks, e.g. saving the config, they are
+	/// shown after the current message is handled.
+	deferred_notices: Vec<Notice>,
+	/// Generation of the latest scan of dictionary directories, results of older
+	/// scans are dropped.
+	scan_generation: u64,
+}
+
+/// Arguments to start the application with.
+#[derive(Debug, Clone)]
+pub struct Flags {
+	/// Command line search term.
+	pub term: String,
+	pub store: DictStore,
 }
 
 /// Messages emitted by the application and its widgets.
@@ -60,20 +137,106 @@ pub enum Message {
 	ToggleContextPage(ContextPage),
 	UpdateConfig(Config),
 	LaunchUrl(String),
+	/// Open a link in an entry, which may be an internal link.
+	OpenLink(String),
+	Key(Modifiers, Key),
 	ChangeSearch(String),
 	Search,
-	SearchResult(Vec<String>),
+	SearchResult(SearchOutput),
+	/// The relative vertical offset of search results in the nav bar.
+	ResultsScrolled(f32),
+	/// Select the search mode by its index in `SearchMode::ALL`.
+	SelectSearchMode(usize),
 	// messages for import
-	OpenImportDialog,
-	DictFileSelected(Url),
+	/// Open the file chooser for dictionary files and archives, or for directories if
+	/// it's `true`.
+	OpenImportDialog(bool),
+	DictFilesSelected(Vec<Url>),
 	ImportCancelled,
 	ImportError(String),
-	ODictCopied(odict::Dictionary, PathBuf),
+	DictsImported(Vec<ImportReport>),
+	/// Resolve the conflict of the import report at the index.
+	ResolveConflict(usize, ConflictResolution),
+	ConflictResolved(usize, ImportStatus),
+	/// Close the import report, conflicts left are skipped.
+	CloseImportReport,
 	// messages for load
 	SelectDict(usize),
-	LoadDict((usize, Dictionary)),
-	LoadError(String),
-	DictNotCompatible((usize, SemanticVersion)),
+	SelectAllDicts,
+	/// Toggle whether accented letters are distinguished when searching.
+	ToggleAccentSensitive,
+	LoadDict((PathBuf, Dictionary)),
+	LoadError((PathBuf, Error)),
+	DictNotCompatible((PathBuf, SemanticVersion)),
+	// messages for history
+	HistoryBack,
+	HistoryForward,
+	HistoryGoTo(usize),
+	ClearHistory,
+	ExportHistory,
+	Exported(PathBuf),
+	ExportError(String),
+	// messages for word lists
+	ToggleStar(WordListItem),
+	SelectWordList(usize),
+	ChangeNewListName(String),
+	CreateWordList,
+	DeleteWordList,
+	MoveWordListItem(usize, usize),
+	RemoveWordListItem(usize),
+	ShowWordListItem(WordListItem),
+	ExportWordList(Format),
+	// messages for the dictionary manager
+	MoveDict(usize, usize),
+	ToggleDictHidden(usize),
+	EditDictName(usize),
+	ChangeDictName(String),
+	CancelRenameDict,
+	RenameDict,
+	/// The dictionary at the first path is renamed from the name and moved to the
+	/// second path, and loaded again.
+	DictRenamed(PathBuf, PathBuf, String, Dictionary),
+	RenameError(String),
+	DeleteDict(usize),
+	ConfirmDeleteDict,
+	CancelDeleteDict,
+	ShowDictInfo(usize),
+	DictInfoLoaded(DictInfo),
+	DictInfoError(String),
+	/// Open the file chooser for extra dictionary directories.
+	AddDictDirs,
+	DictDirsSelected(Vec<Url>),
+	DictDirsError(String),
+	RemoveDictDir(usize),
+	/// Dictionaries found by the scan of the generation.
+	DictsScanned(u64, Vec<PathBuf>),
+	// messages for notices
+	CloseToast(ToastId),
+	/// Open the log page with details of the notice of the id.
+	ShowNotice(u64),
+	ToggleNoticeDetails(u64),
+	ClearNotices,
+}
+
+/// The entry of the selected term in one dictionary.
+pub struct TermSection {
+	pub dict_name: String,
+	pub entry: Entry,
+	md_items: Vec<markdown::Item>,
+}
+
+impl TermSection {
+	#[must_use]
+	pub fn new(dict_name: String, entry: Entry) -> Self {
+		let md_content = render_entry(&entry, Format::Markdown, Some(&dict_name));
+		let md_items = markdown::parse(&md_content).collect();
+
+		Self {
+			dict_name,
+			entry,
+			md_items,
+		}
+	}
 }
 
 /// Create a COSMIC application from the app model
@@ -81,8 +244,7 @@ impl cosmic::Application for AppModel {
 	/// The async executor that will be used to run your application's commands.
 	type Executor = cosmic::executor::Default;
 
-	/// Command line search term
-	type Flags = String;
+	type Flags = Flags;
 
 	/// Messages which the application and its widgets will emit.
 	type Message = Message;
@@ -101,39 +263,80 @@ impl cosmic::Application for AppModel {
 	fn init(core: cosmic::Core, flags: Self::Flags) -> (Self, Task<cosmic::Action<Self::Message>>) {
 		let _span = info_span!("init").entered();
 		let t0 = now();
-		let config_manager = cosmic_config::Config::new(Self::APP_ID, Config::VERSION).unwrap();
-
-		let mut app = AppModel {
-			core,
-			context_page: ContextPage::default(),
-			nav: nav_bar::Model::default(),
-			key_binds: HashMap::new(),
-			config: match Config::get_entry(&config_manager) {
+		let config_manager = cosmic_config::Config::new(Self::APP_ID, Config::VERSION)
+			.inspect_err(|why| error!(%why, "error opening app config, changes won't be saved"))
+			.ok();
+		let config = config_manager
+			.as_ref()
+			.map(|config_manager| match Config::get_entry(config_manager) {
 				Ok(config) => config,
 				Err((errors, config)) => {
-					for why in errors {
+					// missing fields are defaults, e.g. in a config of an older version
+					for why in errors.into_iter().filter(cosmic_config::Error::is_err) {
 						error!(%why, "error loading app config");
 					}
 
 					config
 				}
-			},
+			})
+			.unwrap_or_default();
+		let store = flags.store.with_extra_dirs(config.dict_dirs.clone());
+		// the app is single-instance, so no conflict is waiting to be resolved
+		if let Err(err) = remove_pending_files(&store) {
+			error!("failed to remove pending dictionaries: {err}");
+		}
+
+		let mut app = AppModel {
+			core,
+			context_page: ContextPage::default(),
+			nav: nav_bar::Model::default(),
+			key_binds: key_binds(),
+			config,
 			config_manager,
-			dicts: init_app_dicts().unwrap(),
-			dict_entry: None,
-			md_items: Vec::new(),
-			selected_dict_url: None,
+			dicts: store.dicts(),
+			dict_errors: HashMap::new(),
+			sections: Vec::new(),
+			suggestions: Vec::new(),
+			results: SearchCursor::default(),
+			search_generation: Arc::new(AtomicU64::new(0)),
+			search_error: None,
+			search_modes: vec![fl!("prefix"), fl!("wildcard"), fl!("regex")],
+			importing: Vec::new(),
+			import_reports: Vec::new(),
+			history: Vec::new(),
+			history_position: 0,
+			word_lists: WordLists::default(),
+			readonly_paths: HashSet::new(),
+			selected_list: 0,
+			new_list_name: String::new(),
+			renaming_dict: None,
+			deleting_dict: None,
+			dict_info: None,
+			store,
+			toasts: Toasts::new(Message::CloseToast),
+			notices: Vec::new(),
+			expanded_notice: None,
+			deferred_notices: Vec::new(),
+			scan_generation: 0,
 		};
+		app.sort_dicts();
+		app.history = app.load_user_data(app.store.history_path(), history::load);
+		app.word_lists = app.load_user_data(app.store.word_lists_path(), WordLists::load);
 
-		if !flags.is_empty() {
-			app.config
-				.set_search_term(&app.config_manager, flags)
-				.unwrap();
+		if !flags.term.is_empty() {
+			app.config.search_term = flags.term;
+			app.save_config();
 		}
 
+		// start from the last lookup if it's what we are showing
+		app.history_position = match app.history.last() {
+			Some(item) if item.term == app.config.search_term => app.history.len() - 1,
+			_ => app.history.len(),
+		};
+
 		info!("initialized in {:.3}s", elapsed_secs(&t0));
 
-		let command = app.load_selected_dict();
+		let command = Task::batch([app.load_active_dicts(), app.notify_deferred()]);
 
 		(app, command)
 	}
@@ -144,17 +347,43 @@ impl cosmic::Application for AppModel {
 			menu::root(fl!("file")).apply(Element::from),
 			menu::items(
 				&self.key_binds,
-				vec![menu::Item::Button(fl!("import"), None, MenuAction::Import)],
+				vec![
+					menu::Item::Button(fl!("import"), None, MenuAction::Import),
+					menu::Item::Button(fl!("import-folder"), None, MenuAction::ImportFolder),
+				],
 			),
 		);
 		let view_menu = menu::Tree::with_children(
 			menu::root(fl!("view")).apply(Element::from),
 			menu::items(
 				&self.key_binds,
-				vec![menu::Item::Button(fl!("about"), None, MenuAction::About)],
+				vec![
+					menu::Item::CheckBox(
+						fl!("accent-sensitive"),
+						None,
+						self.config.accent_sensitive,
+						MenuAction::ToggleAccentSensitive,
+					),
+					menu::Item::Divider,
+					menu::Item::Button(fl!("dictionaries"), None, MenuAction::Dictionaries),
+					menu::Item::Button(fl!("history"), None, MenuAction::History),
+					menu::Item::Button(fl!("word-lists"), None, MenuAction::WordLists),
+					menu::Item::Button(fl!("log"), None, MenuAction::Log),
+					menu::Item::Button(fl!("about"), None, MenuAction::About),
+				],
+			),
+		);
+		let go_menu = menu::Tree::with_children(
+			menu::root(fl!("go")).apply(Element::from),
+			menu::items(
+				&self.key_binds,
+				vec![
+					menu::Item::Button(fl!("back"), None, MenuAction::HistoryBack),
+					menu::Item::Button(fl!("forward"), None, MenuAction::HistoryForward),
+				],
 			),
 		);
-		let menu_bar = menu::bar(vec![file_menu, view_menu]);
+		let menu_bar = menu::bar(vec![file_menu, view_menu, go_menu]);
 
 		vec![menu_bar.into()]
 	}
@@ -164,8 +393,16 @@ impl cosmic::Application for AppModel {
 			.on_input(Message::ChangeSearch)
 			.on_clear(Message::ChangeSearch(String::new()))
 			.always_active();
+		let mode = SearchMode::ALL
+			.iter()
+			.position(|mode| *mode == self.config.search_mode);
+		let mode_dropdown = widget::dropdown(
+			self.search_modes.as_slice(),
+			mode,
+			Message::SelectSearchMode,
+		);
 
-		vec![search_input.into()]
+		vec![mode_dropdown.into(), search_input.into()]
 	}
 
 	/// Enables the COSMIC application to create a nav bar with this model.
@@ -173,6 +410,40 @@ impl cosmic::Application for AppModel {
 		Some(&self.nav)
 	}
 
+	/// Same as the default nav bar, but it reports scrolling to load more results.
+	fn nav_bar(&self) -> Option<Element<'_, cosmic::Action<Self::Message>>> {
+		if !self.core().nav_bar_active() {
+			return None;
+		}
+
+		let cosmic_theme::Spacing {
+			space_xxs, space_s, ..
+		} = theme::active().cosmic().spacing;
+		let results = widget::segmented_button::vertical(&self.nav)
+			.button_height(32)
+			.button_padding([space_s, space_xxs, space_s, space_xxs])
+			.button_spacing(space_xxs)
+			.spacing(space_xxs)
+			.style(theme::SegmentedButton::TabBar)
+			.on_activate(|id| cosmic::Action::Cosmic(cosmic::app::Action::NavBar(id)));
+		let results = scrollable(results)
+			.on_scroll(|viewport| {
+				cosmic::Action::App(Message::ResultsScrolled(viewport.relative_offset().y))
+			})
+			.height(Length::Fill);
+
+		let mut nav = widget::container(results)
+			.padding(space_xxs)
+			.class(theme::Container::custom(nav_bar::nav_bar_style))
+			.width(Length::Shrink)
+			.height(Length::Shrink);
+		if !self.core().is_condensed() {
+			nav = nav.max_width(280);
+		}
+
+		Some(nav.into())
+	}
+
 	/// Display a context drawer if the context page is requested.
 	fn context_drawer(&self) -> Option<context_drawer::ContextDrawer<'_, Self::Message>> {
 		if !self.core.window.show_context {
@@ -185,11 +456,36 @@ impl cosmic::Application for AppModel {
 				Message::ToggleContextPage(ContextPage::About),
 			)
 			.title(fl!("about")),
+			ContextPage::Dictionaries => context_drawer::context_drawer(
+				self.dicts_page(),
+				Message::ToggleContextPage(ContextPage::Dictionaries),
+			)
+			.title(fl!("dictionaries")),
+			ContextPage::DictInfo => context_drawer::context_drawer(
+				self.dict_info_page(),
+				Message::ToggleContextPage(ContextPage::DictInfo),
+			)
+			.title(fl!("dictionary-info")),
+			ContextPage::History => context_drawer::context_drawer(
+				self.history_page(),
+				Message::ToggleContextPage(ContextPage::History),
+			)
+			.title(fl!("history")),
+			ContextPage::WordLists => context_drawer::context_drawer(
+				self.word_lists_page(),
+				Message::ToggleContextPage(ContextPage::WordLists),
+			)
+			.title(fl!("word-lists")),
+			ContextPage::Log => context_drawer::context_drawer(
+				self.log_page(),
+				Message::ToggleContextPage(ContextPage::Log),
+			)
+			.title(fl!("log")),
 		})
 	}
 
 	fn view_window(&self, _window_id: window::Id) -> Element<'_, Self::Message> {
-		widget::text("Unknown window ID").into()
+		widget::text(fl!("unknown-window")).into()
 	}
 
 	/// Describes the interface based on the current state of the application model.
@@ -198,24 +494,58 @@ impl cosmic::Application for AppModel {
 	/// events received by widgets will be passed to the update method.
 	fn view(&self) -> Element<'_, Self::Message> {
 		#[allow(clippy::from_iter_instead_of_collect)]
-		let dicts = scrollable::horizontal(widget::Row::from_iter(self.dicts.iter().enumerate().map(
-			|(i, d)| {
-				let name = d.name();
-				button::text(name).on_press(Message::SelectDict(i)).into()
-			},
-		)));
+		let dicts = scrollable::horizontal(widget::Row::from_iter(
+			std::iter::once(
+				button::text(fl!("all-dictionaries"))
+					.on_press(Message::SelectAllDicts)
+					.into(),
+			)
+			.chain(
+				self.dicts
+					.iter()
+					.enumerate()
+					.filter(|(_, d)| !self.is_hidden(d))
+					.map(|(i, d)| {
+						let name = d.name();
+						button::text(name).on_press(Message::SelectDict(i)).into()
+					}),
+			),
+		));
 
 		// TODO: use custom widget
 		let term_page = scrollable(self.build_term_page().padding(10));
 		let content = widget::Column::new().push(dicts).push(term_page).spacing(5);
 		let mut content = widget::popover(content).modal(true);
 
-		if let Some(url) = &self.selected_dict_url {
-			let dialog = widget::dialog().body(format!("Importing {url}, please wait."));
+		if let [url] = self.importing.as_slice() {
+			let dialog = widget::dialog().body(fl!("importing", url = url.to_string()));
+			content = content.popup(dialog);
+		} else if !self.importing.is_empty() {
+			let dialog =
+				widget::dialog().body(fl!("importing-items", count = self.importing.len()));
+			content = content.popup(dialog);
+		} else if !self.import_reports.is_empty() {
+			content = content.popup(self.import_report_dialog());
+		} else if let Some(path) = &self.deleting_dict {
+			let name = self
+				.dicts
+				.iter()
+				.find(|d| d.path == *path)
+				.map(LazyDict::name)
+				.unwrap_or_default();
+			let dialog = widget::dialog()
+				.title(fl!("delete-dictionary"))
+				.body(fl!("delete-dictionary-body", name = name.as_str()))
+				.primary_action(
+					button::destructive(fl!("delete")).on_press(Message::ConfirmDeleteDict),
+				)
+				.secondary_action(
+					button::standard(fl!("cancel")).on_press(Message::CancelDeleteDict),
+				);
 			content = content.popup(dialog);
 		}
 
-		content.into()
+		widget::toaster(&self.toasts, content).into()
 	}
 
 	/// Register subscriptions for this application.
@@ -224,27 +554,78 @@ impl cosmic::Application for AppModel {
 	/// emit messages to the application through a channel. They are started at the
 	/// beginning of the application, and persist through its lifetime.
 	fn subscription(&self) -> Subscription<Self::Message> {
-		// Watch for application configuration changes.
-		self.core()
-			.watch_config::<Config>(Self::APP_ID)
-			.map(|update| {
-				for why in update.errors {
-					error!(?why, "app config error");
+		Subscription::batch([
+			// Watch for application configuration changes.
+			self.core()
+				.watch_config::<Config>(Self::APP_ID)
+				.map(|update| {
+					for why in update
+						.errors
+						.into_iter()
+						.filter(cosmic_config::Error::is_err)
+					{
+						error!(?why, "app config error");
+					}
+
+					Message::UpdateConfig(update.config)
+				}),
+			// Key presses are listened even if captured, e.g. by the search input.
+			event::listen_with(|event, _status, _id| match event {
+				Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+					Some(Message::Key(modifiers, key))
 				}
+				_ => None,
+			}),
+		])
+	}
 
-				Message::UpdateConfig(update.config)
-			})
+	/// Called when a nav item is selected.
+	fn on_nav_select(&mut self, id: nav_bar::Id) -> Task<cosmic::Action<Self::Message>> {
+		// Activate the page in the model.
+		self.nav.activate(id);
+
+		if let Some(s) = self.nav.text(id) {
+			let s = s.to_string();
+			self.sections = self.lookup(&s);
+			if !self.sections.is_empty() {
+				self.record_history(s);
+			}
+		}
+
+		self.update_title()
 	}
 
 	/// Handles messages emitted by the application and its widgets.
 	///
 	/// Tasks may be returned for asynchronous execution of code in the background
 	/// on the application's async runtime.
-	#[allow(clippy::too_many_lines)]
 	fn update(&mut self, message: Self::Message) -> Task<cosmic::Action<Self::Message>> {
+		let task = self.handle_message(message);
+
+		Task::batch([task, self.notify_deferred()])
+	}
+}
+
+impl AppModel {
+	/// See [`cosmic::Application::update`].
+	#[allow(clippy::too_many_lines)]
+	fn handle_message(&mut self, message: Message) -> Task<cosmic::Action<Message>> {
 		match message {
 			Message::Unhandled => (),
-			Message::LoadError(msg) => error!("load dictionary error: {msg}"),
+			Message::LoadError((path, err)) => {
+				let name = match self.dicts.iter_mut().find(|d| d.path == path) {
+					Some(dict) => {
+						dict.is_loading = false;
+						dict.name()
+					}
+					None => path.display().to_string(),
+				};
+				let notice =
+					Notice::error(fl!("load-failed", name = name)).details(err.to_string());
+				self.dict_errors.insert(path, err);
+
+				return self.notify(notice);
+			}
 			Message::OpenRepositoryUrl => {
 				_ = open::that_detached(REPOSITORY);
 			}
@@ -258,119 +639,546 @@ impl cosmic::Application for AppModel {
 					self.core.window.show_context = true;
 				}
 			}
-			Message::UpdateConfig(config) => self.config = config,
+			Message::UpdateConfig(config) => {
+				let rescan = config.dict_dirs != self.config.dict_dirs;
+				let refold = config.accent_sensitive != self.config.accent_sensitive;
+				self.config = config;
+				if refold {
+					self.unload_dicts();
+				}
+				if rescan {
+					return self.rescan_dicts();
+				}
+				if refold {
+					return self.load_or_search();
+				}
+			}
+			Message::Key(modifiers, key) => {
+				let action = self
+					.key_binds
+					.iter()
+					.find(|(key_bind, _)| key_bind.matches(modifiers, &key))
+					.map(|(_, action)| *action);
+				if let Some(action) = action {
+					return self.update(menu::action::MenuAction::message(&action));
+				}
+			}
+			Message::OpenLink(url) => {
+				let Some(link) = LookupLink::parse(&url) else {
+					return self.update(Message::LaunchUrl(url));
+				};
+				// stay in "All dictionaries" mode if we are in it
+				let dict = if self.config.search_all {
+					None
+				} else {
+					link.dict
+						.or_else(|| self.selected_dict().map(LazyDict::name))
+				};
+				return self.show_term(link.term, dict);
+			}
 			Message::LaunchUrl(url) => {
 				if let Err(err) = open::that_detached(&url) {
 					error!("failed to open {url:?}: {err}");
 				}
 			}
-			Message::LoadDict((i, dict)) => {
-				self.dicts[i].load(dict);
-				self.dicts[i].is_loading = false;
-				return Task::done(Message::Search).map(cosmic::Action::from);
+			Message::LoadDict((path, dict)) => {
+				// the dictionary may be removed while loading
+				if let Some(lazy_dict) = self.dicts.iter_mut().find(|d| d.path == path) {
+					lazy_dict.is_loading = false;
+					// the folding may be changed while loading
+					if dict.folding() != Folding::new(!self.config.accent_sensitive) {
+						return self.load_active_dicts();
+					}
+					lazy_dict.load(dict);
+					return Task::done(Message::Search).map(cosmic::Action::from);
+				}
 			}
 			Message::ChangeSearch(s) => {
-				self.config
-					.set_search_term(&self.config_manager, s)
-					.unwrap();
+				self.config.search_term = s;
+				self.save_config();
 
-				if let Some(dict) = self.selected_dict() {
-					return if dict.is_loaded() {
-						self.search()
-					} else {
-						self.load_selected_dict()
-					};
-				}
+				let load = self.load_active_dicts();
+				let debounce = Duration::from_millis(self.config.search_debounce);
+				return Task::batch([load, self.search_after(debounce)]);
 			}
 			Message::Search => return self.search(),
-			Message::SearchResult(terms) => {
-				if terms.is_empty() {
+			Message::SearchResult(output) => {
+				// results of an outdated search
+				if output.generation != self.search_generation.load(Ordering::Relaxed) {
 					return Task::none();
 				}
-				let mut iter = terms.into_iter();
-				self.nav.insert().text(iter.next().unwrap()).activate();
-				for term in iter {
+
+				self.nav.clear();
+				self.results = output.cursor;
+				self.suggestions = output.suggestions;
+				self.search_error = output.error;
+				self.sections = output
+					.entries
+					.into_iter()
+					.map(|(dict_name, entry)| TermSection::new(dict_name, entry))
+					.collect();
+				// matches of patterns are shown without being looked up by the user
+				if !self.sections.is_empty() && output.is_lookup {
+					self.record_history(output.query);
+				}
+
+				let mut terms = output.terms.into_iter();
+				if let Some(first) = terms.next() {
+					self.nav.insert().text(first).activate();
+				}
+				for term in terms {
 					self.nav.insert().text(term);
 				}
 				return self.update_title();
 			}
+			Message::ResultsScrolled(offset) => {
+				// load more results before reaching the end
+				if offset > 0.9 && !self.results.is_exhausted() {
+					let mut results = std::mem::take(&mut self.results);
+					let dicts: Vec<&LazyDict> = self.active_dicts().collect();
+					for term in results.next_page(&dicts, RESULTS_PAGE_SIZE) {
+						self.nav.insert().text(term);
+					}
+					self.results = results;
+				}
+			}
+			Message::SelectSearchMode(i) => {
+				if let Some(mode) = SearchMode::ALL.get(i) {
+					self.config.search_mode = *mode;
+					self.save_config();
+					return self.search();
+				}
+			}
 			Message::SelectDict(i) => {
-				if i == self.config.selected_index {
+				if i == self.config.selected_index && !self.config.search_all {
 					return Task::none();
 				}
-				self.config
-					.set_selected_index(&self.config_manager, i)
-					.unwrap();
+				self.config.selected_index = i;
+				self.config.search_all = false;
+				self.save_config();
 
-				return if self.selected_dict().unwrap().is_loaded() {
-					self.search()
-				} else {
-					self.load_selected_dict()
-				};
+				return self.load_or_search();
 			}
-			Message::OpenImportDialog => {
-				return cosmic::task::future(async move {
-					info!("opening new dialog");
+			Message::SelectAllDicts => {
+				if self.config.search_all {
+					return Task::none();
+				}
+				self.config.search_all = true;
+				self.save_config();
 
-					#[cfg(feature = "rfd")]
-					let filter = FileFilter::new("ODict files").extension("odict");
+				return self.load_or_search();
+			}
+			Message::ToggleAccentSensitive => {
+				self.config.accent_sensitive = !self.config.accent_sensitive;
+				self.save_config();
+				// keys of loaded dictionaries are folded the other way
+				self.unload_dicts();
 
-					#[cfg(feature = "xdg-portal")]
-					let filter = FileFilter::new("ODict files").glob("*.odict");
+				return self.load_or_search();
+			}
+			Message::OpenImportDialog(folders) => {
+				return cosmic::task::future(async move {
+					info!("opening new dialog");
 
-					let dialog = file_chooser::open::Dialog::new()
-						.title("Choose a file")
-						.filter(filter);
+					let dialog = file_chooser::open::Dialog::new();
+					let response = if folders {
+						dialog.title(fl!("choose-folders")).open_folders().await
+					} else {
+						#[cfg(feature = "rfd")]
+						let filter = ArchiveKind::SUFFIXES.iter().fold(
+							FileFilter::new(fl!("dictionary-files"))
+								.extension("odict")
+								.extension("ifo"),
+							// rfd matches only the last extension, e.g. "gz" of ".tar.gz"
+							|filter, (suffix, _)| {
+								filter.extension(suffix.rsplit('.').next().unwrap_or(suffix))
+							},
+						);
+
+						#[cfg(feature = "xdg-portal")]
+						let filter = ArchiveKind::SUFFIXES.iter().fold(
+							FileFilter::new(fl!("dictionary-files"))
+								.glob("*.odict")
+								.glob("*.ifo"),
+							|filter, (suffix, _)| filter.glob(format!("*{suffix}")),
+						);
+
+						dialog
+							.title(fl!("choose-files"))
+							.filter(filter)
+							.open_files()
+							.await
+					};
 
-					match dialog.open_file().await {
-						Ok(response) => Message::DictFileSelected(response.url().to_owned()),
+					match response {
+						Ok(response) => Message::DictFilesSelected(response.urls().to_vec()),
 						Err(file_chooser::Error::Cancelled) => Message::ImportCancelled,
 						Err(err) => Message::ImportError(err.to_string()),
 					}
 				});
 			}
-			Message::DictFileSelected(url) => {
-				info!("selected file: {url}");
-				self.selected_dict_url = Some(url.clone());
-				return create_import_task(url);
+			Message::DictFilesSelected(urls) => {
+				info!("selected {} items to import", urls.len());
+				self.importing.clone_from(&urls);
+				return create_import_task(urls, self.store.clone());
 			}
 			Message::ImportCancelled => info!("import cancelled"),
 			Message::ImportError(err) => {
-				error!("import failed: {err}");
-				self.selected_dict_url = None;
+				self.importing.clear();
+
+				return self.notify(Notice::error(fl!("import-failed")).details(err));
 			}
-			Message::ODictCopied(odict, path) => {
-				let mut dict = LazyDict::new(path);
-				dict.load(odict.into());
-				self.dicts.push(dict);
-				self.selected_dict_url = None;
+			Message::DictsImported(reports) => {
+				self.importing.clear();
+				let mut failures = Vec::new();
+				for report in &reports {
+					match &report.status {
+						ImportStatus::Imported(path) => self.add_imported_dict(path.clone()),
+						ImportStatus::Failed(err) => {
+							failures.push(format!("{}: {err}", report.source.display()));
+						}
+						_ => {}
+					}
+				}
+				self.import_reports = reports;
+
+				let search = self.load_or_search();
+				if failures.is_empty() {
+					return search;
+				}
+				let notice = Notice::error(fl!("import-files-failed", count = failures.len()))
+					.details(failures.join("\n"));
+				return Task::batch([search, self.notify(notice)]);
 			}
-			Message::DictNotCompatible((index, version)) => {
-				error!("dict {index} file version not compatible: {version}");
-				self.dicts.remove(index);
+			Message::ResolveConflict(i, resolution) => {
+				if let Some(ImportStatus::Conflict(conflict)) =
+					self.import_reports.get(i).map(|report| &report.status)
+				{
+					return create_resolve_task(i, conflict.clone(), resolution);
+				}
 			}
-		}
-		Task::none()
-	}
+			Message::ConflictResolved(i, status) => {
+				if let ImportStatus::Imported(path) = &status {
+					self.add_imported_dict(path.clone());
+				}
+				if let Some(report) = self.import_reports.get_mut(i) {
+					report.status = status;
+				}
 
-	/// Called when a nav item is selected.
-	fn on_nav_select(&mut self, id: nav_bar::Id) -> Task<cosmic::Action<Self::Message>> {
-		// Activate the page in the model.
-		self.nav.activate(id);
+				return self.load_or_search();
+			}
+			Message::CloseImportReport => {
+				for report in self.import_reports.drain(..) {
+					if let ImportStatus::Conflict(conflict) = report.status
+						&& let Err(err) = conflict.resolve(ConflictResolution::Skip)
+					{
+						error!("failed to skip {}: {err}", report.source.display());
+					}
+				}
+			}
+			Message::HistoryBack => {
+				if self.history_position > 0 {
+					return self.go_to_history(self.history_position - 1);
+				}
+			}
+			Message::HistoryForward => {
+				if self.history_position + 1 < self.history.len() {
+					return self.go_to_history(self.history_position + 1);
+				}
+			}
+			Message::HistoryGoTo(i) => return self.go_to_history(i),
+			Message::ClearHistory => {
+				self.history = Vec::new();
+				self.save_history();
+				self.history_position = 0;
+			}
+			Message::ExportHistory => {
+				return create_export_task(
+					"history.csv".to_string(),
+					history::to_csv(&self.history),
+				);
+			}
+			Message::ToggleStar(item) => {
+				if let Some(list) = self.word_lists.lists.get_mut(self.selected_list) {
+					list.toggle(item);
+					self.save_word_lists();
+				}
+			}
+			Message::SelectWordList(i) => self.selected_list = i,
+			Message::ChangeNewListName(name) => self.new_list_name = name,
+			Message::CreateWordList => {
+				let name = self.new_list_name.trim().to_string();
+				if !name.is_empty() {
+					self.selected_list = self.word_lists.add_list(name);
+					self.new_list_name.clear();
+					self.save_word_lists();
+				}
+			}
+			Message::DeleteWordList => {
+				self.word_lists.remove_list(self.selected_list);
+				self.selected_list = 0;
+				self.save_word_lists();
+			}
+			Message::MoveWordListItem(from, to) => {
+				if let Some(list) = self.word_lists.lists.get_mut(self.selected_list) {
+					list.move_item(from, to);
+					self.save_word_lists();
+				}
+			}
+			Message::RemoveWordListItem(i) => {
+				if let Some(list) = self.word_lists.lists.get_mut(self.selected_list)
+					&& i < list.items.len()
+				{
+					list.items.remove(i);
+					self.save_word_lists();
+				}
+			}
+			Message::ShowWordListItem(item) => return self.show_term(item.term, Some(item.dict)),
+			Message::ExportWordList(format) => {
+				if let Some(list) = self.word_lists.lists.get(self.selected_list) {
+					let (file_name, content) = match format {
+						Format::Text => (format!("{}.csv", list.name), list.to_csv()),
+						Format::Markdown => (format!("{}.md", list.name), list.to_markdown()),
+					};
+					return create_export_task(file_name, content);
+				}
+			}
+			Message::Exported(path) => {
+				let path = path.display().to_string();
+				return self.notify(Notice::info(fl!("exported", path = path)));
+			}
+			Message::ExportError(err) => {
+				return self.notify(Notice::error(fl!("export-failed")).details(err));
+			}
+			Message::DictNotCompatible((path, version)) => {
+				let name = self
+					.dicts
+					.iter()
+					.find(|d| d.path == path)
+					.map_or_else(|| path.display().to_string(), LazyDict::name);
+				let notice = Notice::warning(fl!(
+					"dict-not-compatible",
+					name = name,
+					version = version.to_string(),
+					required = MINIMAL_ODICT_VERSION.to_string()
+				))
+				.details(path.display().to_string());
+				let selected = self.selected_dict().map(|d| d.path.clone());
+				self.dicts.retain(|d| d.path != path);
+				self.reselect_dict(selected.as_deref());
 
-		if let Some(dict) = self.dicts.get_mut(self.config.selected_index)
-			&& let Some(s) = self.nav.text(id)
-		{
-			self.dict_entry = dict.get(s).unwrap().cloned();
-			self.update_md_items();
-		}
+				return self.notify(notice);
+			}
+			Message::MoveDict(from, to) => {
+				if from < self.dicts.len() && to < self.dicts.len() {
+					let selected = self.selected_dict().map(|d| d.path.clone());
+					let dict = self.dicts.remove(from);
+					self.dicts.insert(to, dict);
+					let order = self.dicts.iter().map(|d| d.path.clone()).collect();
+					self.config.dict_order = order;
+					self.save_config();
+					self.reselect_dict(selected.as_deref());
+					return self.search();
+				}
+			}
+			Message::ToggleDictHidden(i) => {
+				if let Some(dict) = self.dicts.get(i) {
+					let mut hidden = self.config.hidden_dicts.clone();
+					if let Some(j) = hidden.iter().position(|path| *path == dict.path) {
+						hidden.remove(j);
+					} else {
+						hidden.push(dict.path.clone());
+					}
+					self.config.hidden_dicts = hidden;
+					self.save_config();
 
-		self.update_title()
-	}
-}
+					return self.load_or_search();
+				}
+			}
+			Message::EditDictName(i) => {
+				self.renaming_dict = self.dicts.get(i).map(|d| (d.path.clone(), d.name()));
+			}
+			Message::ChangeDictName(name) => {
+				if let Some((_, new_name)) = &mut self.renaming_dict {
+					*new_name = name;
+				}
+			}
+			Message::CancelRenameDict => self.renaming_dict = None,
+			Message::RenameDict => {
+				let Some((path, name)) = self.renaming_dict.take() else {
+					return Task::none();
+				};
+				let name = name.trim().to_string();
+				// names identify dictionaries in history and word lists
+				if name.is_empty() {
+					return self.notify(Notice::error(fl!("dict-name-empty")));
+				}
+				if self.dicts.iter().any(|d| d.name() == name) {
+					return self.notify(Notice::error(fl!("dict-name-taken", name = name)));
+				}
 
-impl AppModel {
-	const APP_NAME: &'static str = "mydict";
+				let folding = Folding::new(!self.config.accent_sensitive);
+				return create_rename_task(path, name, folding, self.store.index_cache());
+			}
+			Message::DictRenamed(path, new_path, old_name, dict) => {
+				let Some(lazy_dict) = self.dicts.iter_mut().find(|d| d.path == path) else {
+					return Task::none();
+				};
+				*lazy_dict = LazyDict::new(new_path.clone());
+				lazy_dict.load(dict);
+				let new_name = lazy_dict.name();
+
+				// the order and hidden dictionaries are kept by path
+				if new_path != path {
+					let rename = |p: &PathBuf| {
+						if *p == path {
+							new_path.clone()
+						} else {
+							p.clone()
+						}
+					};
+					self.config.dict_order = self.config.dict_order.iter().map(rename).collect();
+					self.config.hidden_dicts =
+						self.config.hidden_dicts.iter().map(rename).collect();
+					self.dict_errors.remove(&path);
+					self.save_config();
+				}
+
+				if history::rename_dict(&mut self.history, &old_name, &new_name) {
+					self.save_history();
+				}
+				if self.word_lists.rename_dict(&old_name, &new_name) {
+					self.save_word_lists();
+				}
+
+				return self.search();
+			}
+			Message::RenameError(err) => {
+				return self.notify(Notice::error(fl!("rename-failed")).details(err));
+			}
+			Message::DeleteDict(i) => {
+				self.deleting_dict = self.dicts.get(i).map(|d| d.path.clone());
+			}
+			Message::CancelDeleteDict => self.deleting_dict = None,
+			Message::ConfirmDeleteDict => {
+				let Some(path) = self.deleting_dict.take() else {
+					return Task::none();
+				};
+				if let Err(err) = std::fs::remove_file(&path) {
+					let notice = Notice::error(fl!("delete-failed"))
+						.details(format!("{}: {err}", path.display()));
+					return self.notify(notice);
+				}
+				info!("deleted {path:?}");
+				if let Err(err) = self.store.index_cache().remove(&path) {
+					error!("failed to remove index cache of {path:?}: {err}");
+				}
+
+				let selected = self.selected_dict().map(|d| d.path.clone());
+				self.dicts.retain(|d| d.path != path);
+				self.dict_errors.remove(&path);
+				let mut order = self.config.dict_order.clone();
+				order.retain(|p| *p != path);
+				self.config.dict_order = order;
+				let mut hidden = self.config.hidden_dicts.clone();
+				hidden.retain(|p| *p != path);
+				self.config.hidden_dicts = hidden;
+				self.save_config();
+				self.reselect_dict(selected.as_deref());
+
+				return self.load_or_search();
+			}
+			Message::ShowDictInfo(i) => {
+				if let Some(dict) = self.dicts.get(i) {
+					self.dict_info = None;
+					self.context_page = ContextPage::DictInfo;
+					self.core.window.show_context = true;
+
+					let folding = Folding::new(!self.config.accent_sensitive);
+					return create_info_task(
+						dict.path.clone(),
+						dict.dictionary().cloned(),
+						folding,
+						self.store.index_cache(),
+					);
+				}
+			}
+			Message::DictInfoLoaded(info) => self.dict_info = Some(info),
+			Message::DictInfoError(err) => {
+				self.context_page = ContextPage::Dictionaries;
+
+				return self.notify(Notice::error(fl!("dict-info-failed")).details(err));
+			}
+			Message::AddDictDirs => {
+				return cosmic::task::future(async move {
+					let response = file_chooser::open::Dialog::new()
+						.title(fl!("choose-folders"))
+						.open_folders()
+						.await;
+
+					match response {
+						Ok(response) => Message::DictDirsSelected(response.urls().to_vec()),
+						Err(file_chooser::Error::Cancelled) => Message::Unhandled,
+						Err(err) => Message::DictDirsError(err.to_string()),
+					}
+				});
+			}
+			Message::DictDirsSelected(urls) => {
+				let mut dirs = self.config.dict_dirs.clone();
+				for url in urls {
+					match url.to_file_path() {
+						Ok(dir) if !dirs.contains(&dir) => dirs.push(dir),
+						Ok(_) => {}
+						Err(()) => self.deferred_notices.push(
+							Notice::error(fl!("add-folder-failed"))
+								.details(fl!("not-local-folder", url = url.to_string())),
+						),
+					}
+				}
+				if dirs != self.config.dict_dirs {
+					self.config.dict_dirs = dirs;
+					self.save_config();
+					return self.rescan_dicts();
+				}
+			}
+			Message::DictDirsError(err) => {
+				return self.notify(Notice::error(fl!("add-folder-failed")).details(err));
+			}
+			Message::RemoveDictDir(i) => {
+				if i < self.config.dict_dirs.len() {
+					self.config.dict_dirs.remove(i);
+					self.save_config();
+					return self.rescan_dicts();
+				}
+			}
+			Message::DictsScanned(generation, paths) => {
+				if generation == self.scan_generation {
+					return self.apply_scan(paths);
+				}
+			}
+			Message::CloseToast(id) => self.toasts.remove(id),
+			Message::ShowNotice(id) => {
+				// it may be dropped from the log already
+				if self.notices.iter().any(|notice| notice.id == id) {
+					self.expanded_notice = Some(id);
+					self.context_page = ContextPage::Log;
+					self.core.window.show_context = true;
+				}
+			}
+			Message::ToggleNoticeDetails(id) => {
+				self.expanded_notice = if self.expanded_notice == Some(id) {
+					None
+				} else {
+					Some(id)
+				};
+			}
+			Message::ClearNotices => {
+				self.notices.clear();
+				self.expanded_notice = None;
+			}
+		}
+		Task::none()
+	}
 
 	/// The about page for this app.
 	#[allow(clippy::unused_self)]
@@ -407,75 +1215,304 @@ impl AppModel {
 			.into()
 	}
 
-	/// Update markdown items from current dictionary entry.
-	fn update_md_items(&mut self) {
-		if let Some(entry) = &self.dict_entry {
-			let mut md_lines = Vec::new();
-			md_lines.push(format!("# {}", entry.term));
+	/// The history page for this app.
+	pub fn history_page(&self) -> Element<'_, Message> {
+		let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
 
-			for (i, ety) in entry.etymologies.iter().enumerate() {
-				if entry.etymologies.len() > 1 {
-					md_lines.push(format!("## Etymology #{}", i + 1));
-				}
-				if let Some(desc) = &ety.description {
-					for p in desc.lines() {
-						md_lines.push(format!("{p}\n"));
-					}
-				}
-				for sense in &ety.senses {
-					md_lines.push(format!("**{}**\n", sense.pos));
-					for (j, def) in sense.definitions.iter().enumerate() {
-						let alphabetic_numbering = |i| (b'a' + u8::try_from(i).unwrap()) as char;
-						match def {
-							DefinitionType::Definition(def) => {
-								md_lines.push(format!("{}. {}\n", j + 1, def.value));
-								for example in &def.examples {
-									md_lines.push(format!("\t- {}\n", example.value));
-								}
-
-								if !def.notes.is_empty() {
-									md_lines.push("\tNotes\n".to_string());
-								}
-
-								for (k, note) in def.notes.iter().enumerate() {
-									md_lines.push(format!(
-										"\t{}. {}\n",
-										alphabetic_numbering(k),
-										note.value
-									));
-								}
-							}
-							DefinitionType::Group(group) => {
-								md_lines.push(format!("{}. {}\n", j + 1, group.description));
-
-								for (k, def) in group.definitions.iter().enumerate() {
-									md_lines.push(format!(
-										"\t{}. {}\n",
-										alphabetic_numbering(k),
-										def.value
-									));
-									for example in &def.examples {
-										md_lines.push(format!("\t\t- {}\n", example.value));
-									}
-
-									if !def.notes.is_empty() {
-										md_lines.push("\t\tNotes\n".to_string());
-									}
-
-									for (l, note) in def.notes.iter().enumerate() {
-										md_lines.push(format!("\t\t{}. {}\n", l + 1, note.value));
-									}
-								}
-							}
-						}
-					}
-				}
+		let actions = widget::Row::new()
+			.push(button::standard(fl!("clear-history")).on_press(Message::ClearHistory))
+			.push(button::standard(fl!("export-history")).on_press(Message::ExportHistory))
+			.spacing(space_xxs);
+
+		let mut items = widget::Column::new().spacing(space_xxs);
+		for (i, item) in self.history.iter().enumerate().rev() {
+			let dict = item.dict.clone().unwrap_or_else(|| fl!("all-dictionaries"));
+			let label = format!(
+				"{} — {dict} — {}",
+				item.term,
+				format_timestamp(item.timestamp)
+			);
+			let mut button = button::text(label).on_press(Message::HistoryGoTo(i));
+			if i == self.history_position {
+				button = button.class(theme::Button::Suggested);
+			}
+			items = items.push(button);
+		}
+
+		widget::Column::new()
+			.push(actions)
+			.push(items)
+			.spacing(space_xxs)
+			.into()
+	}
+
+	/// The log page for this app, newest notices first.
+	pub fn log_page(&self) -> Element<'_, Message> {
+		let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+		let mut items = widget::Column::new().spacing(space_xxs);
+		for notice in self.notices.iter().rev() {
+			let mut button = button::text(notice.label());
+			if notice.details.is_some() {
+				button = button.on_press(Message::ToggleNoticeDetails(notice.id));
+			}
+			items = items.push(button);
+			if self.expanded_notice == Some(notice.id)
+				&& let Some(details) = &notice.details
+			{
+				items = items.push(text::body(details.as_str()));
+			}
+		}
+
+		widget::Column::new()
+			.push(button::standard(fl!("clear-log")).on_press(Message::ClearNotices))
+			.push(items)
+			.spacing(space_xxs)
+			.into()
+	}
+
+	/// The word lists page for this app.
+	pub fn word_lists_page(&self) -> Element<'_, Message> {
+		let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+		let mut lists = widget::Row::new().spacing(space_xxs);
+		for (i, list) in self.word_lists.lists.iter().enumerate() {
+			let mut button = button::text(list.name.as_str()).on_press(Message::SelectWordList(i));
+			if i == self.selected_list {
+				button = button.class(theme::Button::Suggested);
 			}
+			lists = lists.push(button);
+		}
+
+		let new_list = widget::Row::new()
+			.push(
+				widget::text_input(fl!("new-word-list"), &self.new_list_name)
+					.on_input(Message::ChangeNewListName),
+			)
+			.push(button::standard(fl!("create")).on_press(Message::CreateWordList))
+			.spacing(space_xxs);
+
+		let mut page = widget::Column::new()
+			.push(scrollable::horizontal(lists))
+			.push(new_list)
+			.spacing(space_xxs);
+
+		if let Some(list) = self.word_lists.lists.get(self.selected_list) {
+			let actions = widget::Row::new()
+				.push(
+					button::standard(fl!("export-csv"))
+						.on_press(Message::ExportWordList(Format::Text)),
+				)
+				.push(
+					button::standard(fl!("export-markdown"))
+						.on_press(Message::ExportWordList(Format::Markdown)),
+				)
+				.push(button::destructive(fl!("delete")).on_press(Message::DeleteWordList))
+				.spacing(space_xxs);
+			page = page.push(actions);
+
+			for (i, item) in list.items.iter().enumerate() {
+				let row = widget::Row::new()
+					.push(
+						button::text(format!("{} — {}", item.term, item.dict))
+							.on_press(Message::ShowWordListItem(item.clone()))
+							.width(Length::Fill),
+					)
+					.push(
+						button::icon(widget::icon::from_name("go-up-symbolic"))
+							.on_press_maybe((i > 0).then(|| Message::MoveWordListItem(i, i - 1))),
+					)
+					.push(
+						button::icon(widget::icon::from_name("go-down-symbolic")).on_press_maybe(
+							(i + 1 < list.items.len()).then(|| Message::MoveWordListItem(i, i + 1)),
+						),
+					)
+					.push(
+						button::icon(widget::icon::from_name("edit-delete-symbolic"))
+							.on_press(Message::RemoveWordListItem(i)),
+					)
+					.align_y(Alignment::Center)
+					.spacing(space_xxs);
+				page = page.push(row);
+			}
+		}
+
+		page.into()
+	}
+
+	/// The dictionary manager page for this app.
+	pub fn dicts_page(&self) -> Element<'_, Message> {
+		let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+		let mut page = widget::Column::new().spacing(space_xxs);
+		for (i, dict) in self.dicts.iter().enumerate() {
+			// dictionaries in system data directories are read-only
+			let is_imported = self.store.is_imported(&dict.path);
+			let mut row = widget::Row::new()
+				.align_y(Alignment::Center)
+				.spacing(space_xxs);
+
+			row = match &self.renaming_dict {
+				Some((path, name)) if *path == dict.path => row
+					.push(
+						widget::text_input(fl!("dictionary-name"), name)
+							.on_input(Message::ChangeDictName)
+							.width(Length::Fill),
+					)
+					.push(
+						button::icon(widget::icon::from_name("object-select-symbolic"))
+							.on_press(Message::RenameDict),
+					)
+					.push(
+						button::icon(widget::icon::from_name("window-close-symbolic"))
+							.on_press(Message::CancelRenameDict),
+					),
+				_ => row.push(text(dict.name()).width(Length::Fill)).push(
+					button::icon(widget::icon::from_name("document-edit-symbolic"))
+						.on_press_maybe(is_imported.then_some(Message::EditDictName(i))),
+				),
+			};
+
+			let visibility_icon = if self.is_hidden(dict) {
+				"view-conceal-symbolic"
+			} else {
+				"view-reveal-symbolic"
+			};
+			row = row
+				.push(
+					button::icon(widget::icon::from_name("dialog-information-symbolic"))
+						.on_press(Message::ShowDictInfo(i)),
+				)
+				.push(
+					button::icon(widget::icon::from_name("go-up-symbolic"))
+						.on_press_maybe((i > 0).then(|| Message::MoveDict(i, i - 1))),
+				)
+				.push(
+					button::icon(widget::icon::from_name("go-down-symbolic")).on_press_maybe(
+						(i + 1 < self.dicts.len()).then(|| Message::MoveDict(i, i + 1)),
+					),
+				)
+				.push(
+					button::icon(widget::icon::from_name(visibility_icon))
+						.on_press(Message::ToggleDictHidden(i)),
+				)
+				.push(
+					button::icon(widget::icon::from_name("edit-delete-symbolic"))
+						.on_press_maybe(is_imported.then_some(Message::DeleteDict(i))),
+				);
+			page = page.push(row);
+		}
+
+		page = page.push(text::heading(fl!("dictionary-folders")));
+		for (i, dir) in self.config.dict_dirs.iter().enumerate() {
+			let row = widget::Row::new()
+				.push(text(dir.display().to_string()).width(Length::Fill))
+				.push(
+					button::icon(widget::icon::from_name("edit-delete-symbolic"))
+						.on_press(Message::RemoveDictDir(i)),
+				)
+				.align_y(Alignment::Center)
+				.spacing(space_xxs);
+			page = page.push(row);
+		}
+		page = page.push(button::standard(fl!("add-folder")).on_press(Message::AddDictDirs));
+
+		page.into()
+	}
+
+	/// The information page of the dictionary chosen in the dictionary manager.
+	pub fn dict_info_page(&self) -> Element<'_, Message> {
+		let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
 
-			let md_content = md_lines.join("\n");
-			self.md_items = markdown::parse(&md_content).collect();
+		let mut page = widget::Column::new()
+			.push(
+				button::standard(fl!("back"))
+					.on_press(Message::ToggleContextPage(ContextPage::Dictionaries)),
+			)
+			.spacing(space_xxs);
+		let Some(info) = &self.dict_info else {
+			return page.push(text(fl!("loading"))).into();
+		};
+
+		for (field, value) in info.fields() {
+			let row = widget::Row::new()
+				.push(text::heading(info_field_label(field)).width(Length::FillPortion(1)))
+				.push(text(value).width(Length::FillPortion(2)))
+				.spacing(space_xxs);
+			page = page.push(row);
+		}
+
+		page.into()
+	}
+
+	/// The dialog listing what's imported and what failed in the last import.
+	fn import_report_dialog(&self) -> widget::Dialog<'_, Message> {
+		let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
+
+		let imported = self
+			.import_reports
+			.iter()
+			.filter(|report| matches!(report.status, ImportStatus::Imported(_)))
+			.count();
+		let mut list = widget::Column::new().spacing(space_xxs);
+		for (i, report) in self.import_reports.iter().enumerate() {
+			let source = report.source.display();
+			let line = match &report.status {
+				ImportStatus::Imported(_) => format!("✓ {source}"),
+				ImportStatus::Unchanged(_) => format!("✓ {source}: {}", fl!("import-unchanged")),
+				ImportStatus::Skipped => format!("– {source}: {}", fl!("import-skipped")),
+				ImportStatus::Failed(err) => format!("✗ {source}: {err}"),
+				ImportStatus::Conflict(conflict) => {
+					let describe = |info: &DictFileInfo, hash: u64| {
+						format!(
+							"ODict {}, {}, {hash:016x}",
+							info.version,
+							format_size(info.size)
+						)
+					};
+					let line = format!(
+						"⚠ {source}: {}\n{} → {}",
+						fl!("import-conflict", name = conflict.name.as_str()),
+						describe(&conflict.existing, conflict.hashes.1),
+						describe(&conflict.incoming, conflict.hashes.0),
+					);
+					let actions = [
+						(fl!("replace"), ConflictResolution::Replace),
+						(fl!("keep-both"), ConflictResolution::KeepBoth),
+						(fl!("skip"), ConflictResolution::Skip),
+					]
+					.into_iter()
+					.map(|(label, resolution)| {
+						button::standard(label)
+							.on_press(Message::ResolveConflict(i, resolution))
+							.into()
+					});
+					list = list
+						.push(text(line))
+						.push(widget::Row::with_children(actions).spacing(space_xxs));
+					continue;
+				}
+			};
+			list = list.push(text(line));
+		}
+
+		widget::dialog()
+			.title(fl!(
+				"import-report",
+				imported = imported,
+				total = self.import_reports.len()
+			))
+			.control(scrollable(list).height(Length::Fixed(240.0)))
+			.primary_action(button::suggested(fl!("close")).on_press(Message::CloseImportReport))
+	}
+
+	/// Add a dictionary imported to `path`, which replaces the one loaded from there.
+	fn add_imported_dict(&mut self, path: PathBuf) {
+		self.dict_errors.remove(&path);
+		if let Some(dict) = self.dicts.iter_mut().find(|dict| dict.path == path) {
+			*dict = LazyDict::new(path);
 		} else {
-			self.md_items.clear();
+			self.dicts.push(LazyDict::new(path));
 		}
 	}
 
@@ -483,9 +1520,9 @@ impl AppModel {
 	pub fn update_title(&mut self) -> Task<cosmic::Action<Message>> {
 		let mut window_title = fl!("app-title");
 
-		if let Some(entry) = &self.dict_entry {
+		if let Some(section) = self.sections.first() {
 			window_title.push_str(" — ");
-			window_title.push_str(&entry.term);
+			window_title.push_str(&section.entry.term);
 		}
 
 		if let Some(id) = self.core.main_window_id() {
@@ -495,75 +1532,234 @@ impl AppModel {
 		}
 	}
 
-	/// Load selected dictionary.
-	///
-	/// # Panics
-	///
-	/// Will panic if load dictionary failed.
-	pub fn load_selected_dict(&mut self) -> Task<cosmic::Action<Message>> {
-		self.correct_selected_index();
+	/// Record a lookup of `term` in the dictionaries to search in.
+	fn record_history(&mut self, term: String) {
+		// don't record when navigating in history
+		if self
+			.history
+			.get(self.history_position)
+			.is_some_and(|item| item.term == term)
+		{
+			return;
+		}
 
-		let index = self.config.selected_index;
-		let Some(selected_dict) = self.dicts.get_mut(index) else {
-			info!(
-				"selected index ({}) out of range, dicts size: {}",
-				index,
-				self.dicts.len()
-			);
-			return Task::none();
+		let dict = if self.config.search_all {
+			None
+		} else {
+			self.dicts
+				.get(self.config.selected_index)
+				.map(LazyDict::name)
 		};
+		history::record(&mut self.history, HistoryItem::new(term, dict));
+		self.history_position = self.history.len() - 1;
+		self.save_history();
+	}
 
-		if selected_dict.is_loading {
-			info!("selected dictionary is loading, ignore load request");
+	/// Show the `i`th item in history.
+	fn go_to_history(&mut self, i: usize) -> Task<cosmic::Action<Message>> {
+		let Some(item) = self.history.get(i).cloned() else {
 			return Task::none();
-		}
-		selected_dict.is_loading = true;
+		};
+		self.history_position = i;
 
-		create_load_task(index, selected_dict.path.clone())
+		self.show_term(item.term, item.dict)
 	}
 
-	/// # Panics
-	///
-	/// Will panic if no valid home directory path could be retrieved
-	#[must_use]
-	pub fn project_dirs() -> ProjectDirs {
-		ProjectDirs::from("", "", Self::APP_NAME).unwrap()
+	/// Search `term` in the dictionary named `dict`, or in all dictionaries if `dict` is
+	/// `None`.
+	fn show_term(&mut self, term: String, dict: Option<String>) -> Task<cosmic::Action<Message>> {
+		match &dict {
+			None => self.config.search_all = true,
+			Some(name) => {
+				if let Some(index) = self
+					.dicts
+					.iter()
+					.position(|d| d.name() == *name && !self.is_hidden(d))
+				{
+					self.config.selected_index = index;
+				}
+				self.config.search_all = false;
+			}
+		}
+		self.config.search_term = term;
+		self.save_config();
+
+		self.load_or_search()
 	}
 
-	#[must_use]
-	pub fn local_data_dir() -> PathBuf {
-		Self::project_dirs().data_dir().to_path_buf()
+	/// Save data with `save` to `path`, unless it could not be read when it was
+	/// loaded, see [`Self::load_user_data`].
+	fn save_user_data(
+		&self,
+		path: &Path,
+		save: impl FnOnce(&Path) -> crate::Result<()>,
+	) -> crate::Result<()> {
+		if self.readonly_paths.contains(path) {
+			return Ok(());
+		}
+
+		save(path)
 	}
 
-	#[must_use]
-	pub fn system_data_dir() -> PathBuf {
-		PathBuf::from("/usr/share").join(Self::APP_NAME)
+	fn save_history(&mut self) {
+		let path = self.store.history_path();
+		if let Err(err) = self.save_user_data(&path, |path| history::save(&self.history, path)) {
+			self.deferred_notices
+				.push(Notice::error(fl!("save-history-failed")).details(err.to_string()));
+		}
 	}
 
-	/// Returns a sorted list of all dictionaries under the data directories.
-	///
-	/// # Errors
+	/// Returns the data loaded by `load` from `path`, or the default if it fails.
 	///
-	/// Will return an error if any of the data directories could not be read.
-	pub fn dict_paths() -> anyhow::Result<Vec<PathBuf>> {
-		let mut paths = Vec::new();
+	/// Files which could not be parsed are kept as backups by `load`. Others which
+	/// could not be read are added to `readonly_paths`, so they are not overwritten.
+	fn load_user_data<T: Default>(
+		&mut self,
+		path: PathBuf,
+		load: impl FnOnce(&Path) -> crate::Result<T>,
+	) -> T {
+		load(&path).unwrap_or_else(|err| {
+			let file = path.display().to_string();
+			let notice = match err {
+				Error::Unreadable { backup, reason } => Notice::warning(fl!(
+					"data-backed-up",
+					file = file,
+					backup = backup.display().to_string()
+				))
+				.details(reason),
+				err => {
+					self.readonly_paths.insert(path);
+					Notice::error(fl!("load-data-failed", file = file)).details(err.to_string())
+				}
+			};
+			self.deferred_notices.push(notice);
+
+			T::default()
+		})
+	}
+
+	fn save_word_lists(&mut self) {
+		let path = self.store.word_lists_path();
+		if let Err(err) = self.save_user_data(&path, |path| self.word_lists.save(path)) {
+			self.deferred_notices
+				.push(Notice::error(fl!("save-word-lists-failed")).details(err.to_string()));
+		}
+	}
+
+	/// Load dictionaries to search in, i.e. the selected one or all in "All dictionaries"
+	/// mode.
+	pub fn load_active_dicts(&mut self) -> Task<cosmic::Action<Message>> {
+		self.correct_selected_index();
 
-		for dir in &[Self::local_data_dir(), Self::system_data_dir()] {
-			if !dir.is_dir() {
+		let folding = Folding::new(!self.config.accent_sensitive);
+		let cache = self.store.index_cache();
+		let mut tasks = Vec::new();
+		for i in self.active_dict_indices() {
+			let dict = &mut self.dicts[i];
+			if dict.is_loaded() || dict.is_loading || self.dict_errors.contains_key(&dict.path) {
 				continue;
 			}
+			dict.is_loading = true;
+			tasks.push(create_load_task(dict.path.clone(), folding, cache.clone()));
+		}
 
-			for entry in dir.read_dir()? {
-				let path = entry?.path();
-				if path.is_file() && path.extension().is_some_and(|s| s == "odict") {
-					paths.push(path);
-				}
+		Task::batch(tasks)
+	}
+
+	/// Load dictionaries to search in, and search in those already loaded.
+	fn load_or_search(&mut self) -> Task<cosmic::Action<Message>> {
+		let load = self.load_active_dicts();
+		Task::batch([load, self.search()])
+	}
+
+	/// Indices of dictionaries to search in, hidden ones are left out.
+	fn active_dict_indices(&self) -> Vec<usize> {
+		let indices = if self.config.search_all {
+			0..self.dicts.len()
+		} else {
+			let i = self.config.selected_index.min(self.dicts.len());
+			i..(i + 1).min(self.dicts.len())
+		};
+
+		indices
+			.filter(|i| !self.is_hidden(&self.dicts[*i]))
+			.collect()
+	}
+
+	/// Loaded dictionaries to search in.
+	fn active_dicts(&self) -> impl Iterator<Item = &LazyDict> {
+		self.active_dict_indices()
+			.into_iter()
+			.map(|i| &self.dicts[i])
+			.filter(|dict| dict.is_loaded())
+	}
+
+	/// Whether `dict` is hidden by the user.
+	fn is_hidden(&self, dict: &LazyDict) -> bool {
+		self.config.hidden_dicts.contains(&dict.path)
+	}
+
+	/// Drop loaded dictionaries, e.g. after the folding is changed, so they are
+	/// loaded again when they are searched.
+	fn unload_dicts(&mut self) {
+		for dict in &mut self.dicts {
+			if dict.is_loaded() {
+				*dict = LazyDict::new(dict.path.clone());
 			}
 		}
+	}
+
+	/// Find dictionaries again after dictionary directories are changed, which is done
+	/// in a task as directories may be slow, e.g. network mounts.
+	fn rescan_dicts(&mut self) -> Task<cosmic::Action<Message>> {
+		self.store.extra_dirs.clone_from(&self.config.dict_dirs);
+		self.scan_generation += 1;
+
+		create_scan_task(self.scan_generation, self.store.clone())
+	}
+
+	/// Replace dictionaries with those found by a scan, loaded ones are kept.
+	fn apply_scan(&mut self, paths: Vec<PathBuf>) -> Task<cosmic::Action<Message>> {
+		let selected = self.selected_dict().map(|d| d.path.clone());
+
+		let mut old_dicts: HashMap<PathBuf, LazyDict> = self
+			.dicts
+			.drain(..)
+			.map(|dict| (dict.path.clone(), dict))
+			.collect();
+		self.dicts = paths
+			.into_iter()
+			.map(|path| {
+				old_dicts
+					.remove(&path)
+					.unwrap_or_else(|| LazyDict::new(path))
+			})
+			.collect();
+		self.sort_dicts();
+		self.reselect_dict(selected.as_deref());
 
-		paths.sort();
+		self.load_or_search()
+	}
 
-		Ok(paths)
+	/// Sort dictionaries in the order of `config.dict_order`, others follow by path.
+	fn sort_dicts(&mut self) {
+		let order = &self.config.dict_order;
+		self.dicts.sort_by_key(|dict| {
+			order
+				.iter()
+				.position(|path| *path == dict.path)
+				.unwrap_or(order.len())
+		});
+	}
+
+	/// Returns entries of `term` in dictionaries to search in.
+	fn lookup(&self, term: &str) -> Vec<TermSection> {
+		self.active_dicts()
+			.filter_map(|dict| {
+				let entry = dict.get(term).ok().flatten()?;
+				Some(TermSection::new(dict.name(), entry.clone()))
+			})
+			.collect()
 	}
 
 	#[must_use]
@@ -571,97 +1767,214 @@ impl AppModel {
 		self.dicts.get(self.config.selected_index)
 	}
 
-	/// # Panics
-	///
-	/// Will panic if config update failed.
-	pub fn correct_selected_index(&mut self) {
-		if self.dicts.len() <= self.config.selected_index {
-			info!("reset selected dict index to 0, because there are not enough dicts");
-			self.config
-				.set_selected_index(&self.config_manager, 0)
-				.unwrap();
+	/// Show `notice` in a toast and keep it in the log, the toast of a notice with
+	/// details can open them in the log page.
+	fn notify(&mut self, notice: Notice) -> Task<cosmic::Action<Message>> {
+		notice.trace();
+		if self.notices.len() >= LOG_LIMIT {
+			self.notices.remove(0);
 		}
+
+		let mut toast = Toast::new(notice.summary.clone());
+		if notice.details.is_some() {
+			let id = notice.id;
+			toast = toast.action(fl!("details"), move |_| Message::ShowNotice(id));
+		}
+		self.notices.push(notice);
+
+		self.toasts.push(toast).map(cosmic::Action::App)
 	}
 
-	/// Search term in selected dictionary
-	fn search(&mut self) -> Task<cosmic::Action<Message>> {
-		let _span = debug_span!("search").entered();
-		let t0 = now();
+	/// Show notices in `deferred_notices`.
+	fn notify_deferred(&mut self) -> Task<cosmic::Action<Message>> {
+		let notices = std::mem::take(&mut self.deferred_notices);
+		let tasks: Vec<_> = notices
+			.into_iter()
+			.map(|notice| self.notify(notice))
+			.collect();
 
-		self.nav.clear();
+		Task::batch(tasks)
+	}
 
-		let s = self.config.search_term.trim().to_string();
-		if s.is_empty() {
-			self.dict_entry = None;
-			self.update_md_items();
-			return Task::none();
+	/// Save the config, changes are kept in memory only if it could not be saved.
+	fn save_config(&mut self) {
+		if let Some(config_manager) = &self.config_manager
+			&& let Err(why) = self.config.write_entry(config_manager)
+		{
+			self.deferred_notices
+				.push(Notice::error(fl!("save-config-failed")).details(why.to_string()));
+		}
+	}
+
+	/// Select the first shown dictionary if the selected one doesn't exist or is hidden.
+	pub fn correct_selected_index(&mut self) {
+		let is_valid = self
+			.selected_dict()
+			.is_some_and(|dict| !self.is_hidden(dict));
+		if is_valid {
+			return;
 		}
 
-		if let Some(dict) = self.dicts.get_mut(self.config.selected_index) {
-			let terms = dict.search(&s).unwrap().into_iter().take(1000).collect();
-			self.dict_entry = dict.get(&s).unwrap().cloned();
-			self.update_md_items();
-			debug!(
-				"search \"{}\" in dict {} finished in {:.3}s",
-				s,
-				self.config.selected_index,
-				elapsed_secs(&t0)
-			);
+		let index = self
+			.dicts
+			.iter()
+			.position(|dict| !self.is_hidden(dict))
+			.unwrap_or_default();
+		if index != self.config.selected_index {
+			info!("reset selected dict index to {index}, because it's not shown");
+			self.config.selected_index = index;
+			self.save_config();
+		}
+	}
 
-			return Task::done(Message::SearchResult(terms)).map(cosmic::Action::from);
+	/// Select the dictionary at `selected` again after dictionaries are moved or
+	/// removed, the first shown one is selected if it's gone.
+	fn reselect_dict(&mut self, selected: Option<&Path>) {
+		if let Some(index) =
+			selected.and_then(|path| self.dicts.iter().position(|d| d.path == path))
+			&& index != self.config.selected_index
+		{
+			self.config.selected_index = index;
+			self.save_config();
 		}
+		self.correct_selected_index();
+	}
 
-		error!("dict_index not valid: {}", self.config.selected_index);
-		self.config.selected_index = 0;
-		info!("reset dict_index to 0");
+	/// Search term in dictionaries to search in, which are merged in "All dictionaries"
+	/// mode.
+	fn search(&mut self) -> Task<cosmic::Action<Message>> {
+		self.search_after(Duration::ZERO)
+	}
 
-		self.update_title()
+	/// Search after `debounce` in a worker, results of previous searches are discarded.
+	fn search_after(&mut self, debounce: Duration) -> Task<cosmic::Action<Message>> {
+		let generation = self.search_generation.fetch_add(1, Ordering::Relaxed) + 1;
+
+		let query = self.config.search_term.trim().to_string();
+		if query.is_empty() {
+			self.nav.clear();
+			self.suggestions.clear();
+			self.search_error = None;
+			self.sections.clear();
+			self.results = SearchCursor::default();
+			return self.update_title();
+		}
+
+		create_search_task(SearchRequest {
+			query,
+			mode: self.config.search_mode,
+			dicts: self.active_dicts().cloned().collect(),
+			generation,
+			latest: Arc::clone(&self.search_generation),
+			debounce,
+		})
 	}
 
 	/// Build term page from `ODict` entry
 	fn build_term_page(&self) -> widget::Column<'_, Message, cosmic::Theme> {
 		let mut page = widget::Column::new().push(rule::horizontal(2));
 
-		if self.dict_entry.is_none() {
+		if self.sections.is_empty() {
+			let active = self.active_dict_indices();
+			let is_loading = active.iter().any(|&i| self.dicts[i].is_loading);
+			// only shown if no dictionary to search in could be loaded
+			let load_error = active
+				.iter()
+				.map(|&i| {
+					let dict = &self.dicts[i];
+					let err = self.dict_errors.get(&dict.path)?;
+					Some(fl!(
+						"load-error",
+						name = dict.name(),
+						error = err.to_string()
+					))
+				})
+				.collect::<Option<Vec<_>>>()
+				.filter(|errors| !errors.is_empty())
+				.map(|errors| errors.join("\n"));
 			page = page.push(
-				// FIXME: change selected dictionary doesn't show loading
-				text::title1(match self.selected_dict() {
-					None => "no dictionary found, please import one",
-					Some(dict) => {
-						if dict.is_loading {
-							"Loading..."
-						} else {
-							"Search not found"
-						}
-					}
+				text::title1(if self.dicts.is_empty() {
+					fl!("no-dictionary")
+				} else if is_loading {
+					fl!("loading")
+				} else if let Some(err) = load_error {
+					err
+				} else if let Some(err) = &self.search_error {
+					err.clone()
+				} else {
+					fl!("not-found")
 				})
 				.width(Length::Fill)
 				.align_x(Alignment::Center),
 			);
+
+			if !self.suggestions.is_empty() {
+				page = page.push(
+					text::title4(fl!("did-you-mean"))
+						.width(Length::Fill)
+						.align_x(Alignment::Center),
+				);
+
+				let suggestions = widget::flex_row(
+					self.suggestions
+						.iter()
+						.map(|term| {
+							button::link(term.clone())
+								.on_press(Message::ChangeSearch(term.clone()))
+								.into()
+						})
+						.collect(),
+				)
+				.column_spacing(10);
+				page = page.push(widget::container(suggestions).center_x(Length::Fill));
+			}
 		}
 
-		if !self.md_items.is_empty() {
-			// TODO: handle markdown URL
-			struct DictViewer;
+		struct DictViewer;
 
-			impl<'a, Renderer> markdown::Viewer<'a, Message, cosmic::Theme, Renderer> for DictViewer
-			where
-				Renderer: cosmic::iced::core::text::Renderer<Font = cosmic::iced::core::Font> + 'a,
-				cosmic::Theme: markdown::Catalog + 'a,
-			{
-				fn on_link_click(url: markdown::Uri) -> Message {
-					Message::LaunchUrl(url)
-				}
+		impl<'a, Renderer> markdown::Viewer<'a, Message, cosmic::Theme, Renderer> for DictViewer
+		where
+			Renderer: cosmic::iced::core::text::Renderer<Font = cosmic::iced::core::Font> + 'a,
+			cosmic::Theme: markdown::Catalog + 'a,
+		{
+			fn on_link_click(url: markdown::Uri) -> Message {
+				Message::OpenLink(url)
 			}
+		}
+
+		for section in &self.sections {
+			let item = WordListItem {
+				dict: section.dict_name.clone(),
+				term: section.entry.term.clone(),
+			};
+			// starred entries go to the selected list
+			let is_starred = self
+				.word_lists
+				.lists
+				.get(self.selected_list)
+				.is_some_and(|list| list.contains(&item));
+			let star_icon = if is_starred {
+				"starred-symbolic"
+			} else {
+				"non-starred-symbolic"
+			};
+			let header = widget::Row::new()
+				.push(text::title3(section.dict_name.as_str()).width(Length::Fill))
+				.push(
+					button::icon(widget::icon::from_name(star_icon))
+						.on_press(Message::ToggleStar(item)),
+				)
+				.align_y(Alignment::Center);
+			page = page.push(header);
 
 			let md_view = markdown::view_with(
-				&self.md_items,
+				&section.md_items,
 				markdown::Settings::with_style(markdown::Style::from_palette(
 					cosmic::iced::Theme::TokyoNightStorm.palette(),
 				)),
 				&DictViewer,
 			);
-			page = page.push(md_view);
+			page = page.push(md_view).push(rule::horizontal(2));
 		}
 
 		page.width(Length::Fill).spacing(5)
@@ -673,12 +1986,72 @@ impl AppModel {
 pub enum ContextPage {
 	#[default]
 	About,
+	Dictionaries,
+	DictInfo,
+	History,
+	WordLists,
+	/// Notices shown in toasts before.
+	Log,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MenuAction {
 	Import,
+	ImportFolder,
 	About,
+	Dictionaries,
+	History,
+	HistoryBack,
+	HistoryForward,
+	WordLists,
+	Log,
+	ToggleAccentSensitive,
+}
+
+/// Localized label of a field of [`DictInfo::fields`].
+fn info_field_label(field: InfoField) -> String {
+	match field {
+		InfoField::Name => fl!("info-name"),
+		InfoField::Path => fl!("info-path"),
+		InfoField::FormatVersion => fl!("info-format-version"),
+		InfoField::FileSize => fl!("info-file-size"),
+		InfoField::Compression => fl!("info-compression"),
+		InfoField::Entries => fl!("info-entries"),
+		InfoField::References => fl!("info-references"),
+		InfoField::Senses => fl!("info-senses"),
+		InfoField::Definitions => fl!("info-definitions"),
+		InfoField::Examples => fl!("info-examples"),
+		InfoField::PartsOfSpeech => fl!("info-parts-of-speech"),
+		InfoField::Languages => fl!("info-languages"),
+		InfoField::IndexCached => fl!("info-index-cached"),
+		InfoField::IndexBuilt => fl!("info-index-built"),
+	}
+}
+
+fn key_binds() -> HashMap<KeyBind, MenuAction> {
+	HashMap::from([
+		(
+			KeyBind {
+				modifiers: vec![Modifier::Alt],
+				key: Key::Named(Named::ArrowLeft),
+			},
+			MenuAction::HistoryBack,
+		),
+		(
+			KeyBind {
+				modifiers: vec![Modifier::Alt],
+				key: Key::Named(Named::ArrowRight),
+			},
+			MenuAction::HistoryForward,
+		),
+		(
+			KeyBind {
+				modifiers: vec![Modifier::Ctrl],
+				key: Key::Character("h".into()),
+			},
+			MenuAction::History,
+		),
+	])
 }
 
 impl menu::action::MenuAction for MenuAction {
@@ -687,7 +2060,15 @@ impl menu::action::MenuAction for MenuAction {
 	fn message(&self) -> Self::Message {
 		match self {
 			MenuAction::About => Message::ToggleContextPage(ContextPage::About),
-			MenuAction::Import => Message::OpenImportDialog,
+			MenuAction::Import => Message::OpenImportDialog(false),
+			MenuAction::ImportFolder => Message::OpenImportDialog(true),
+			MenuAction::Dictionaries => Message::ToggleContextPage(ContextPage::Dictionaries),
+			MenuAction::History => Message::ToggleContextPage(ContextPage::History),
+			MenuAction::HistoryBack => Message::HistoryBack,
+			MenuAction::HistoryForward => Message::HistoryForward,
+			MenuAction::WordLists => Message::ToggleContextPage(ContextPage::WordLists),
+			MenuAction::Log => Message::ToggleContextPage(ContextPage::Log),
+			MenuAction::ToggleAccentSensitive => Message::ToggleAccentSensitive,
 		}
 	}
 }
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MIT
+
+use crate::SearchMode;
+use cosmic::cosmic_config::{self, CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry};
+use std::path::PathBuf;
+
+/// Settings of the app, each field is stored in its own file of the version.
+///
+/// Fields missing in the stored config, e.g. those added after it was saved, are
+/// loaded as their defaults, so adding fields keeps the version. It must only be
+/// bumped if the type or meaning of a field changes, which starts from defaults.
+#[derive(Debug, Clone, CosmicConfigEntry, Eq, PartialEq)]
+#[version = 1]
+pub struct Config {
+	pub selected_index: usize,
+	pub search_term: String,
+	/// Search in all dictionaries instead of the selected one.
+	pub search_all: bool,
+	/// Distinguish accented letters when searching, e.g. "café" and "cafe".
+	pub accent_sensitive: bool,
+	/// Milliseconds to wait for more keystrokes before searching.
+	pub search_debounce: u64,
+	pub search_mode: SearchMode,
+	/// Paths of dictionaries in the order they are shown, others follow by path.
+	pub dict_order: Vec<PathBuf>,
+	/// Paths of dictionaries which are neither shown nor searched.
+	pub hidden_dicts: Vec<PathBuf>,
+	/// Extra directories to find dictionaries in recursively, see `DictStore`. They
+	/// are only used by the GUI, the command line interface doesn't read the config.
+	pub dict_dirs: Vec<PathBuf>,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			selected_index: 0,
+			search_term: String::new(),
+			search_all: false,
+			accent_sensitive: false,
+			search_debounce: 150,
+			search_mode: SearchMode::default(),
+			dict_order: Vec::new(),
+			hidden_dicts: Vec::new(),
+			dict_dirs: Vec::new(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::TempDir;
+	use cosmic::cosmic_config::ConfigSet;
+
+	#[test]
+	fn load_old_config() {
+		let dir = TempDir::new("config");
+		let config =
+			cosmic_config::Config::with_custom_path("test", Config::VERSION, dir.path().into())
+				.unwrap();
+		// the fields of the first version
+		config.set("selected_index", 2_usize).unwrap();
+		config.set("search_term", "run").unwrap();
+
+		let (errors, loaded) = Config::get_entry(&config).unwrap_err();
+		assert!(!errors.is_empty());
+		assert!(!errors.iter().any(cosmic_config::Error::is_err));
+		assert_eq!(
+			loaded,
+			Config {
+				selected_index: 2,
+				search_term: "run".to_string(),
+				..Config::default()
+			}
+		);
+
+		let mut saved = loaded.clone();
+		saved.search_mode = SearchMode::Regex;
+		saved.dict_dirs = vec![PathBuf::from("/dicts")];
+		saved.write_entry(&config).unwrap();
+		assert_eq!(Config::get_entry(&config).unwrap(), saved);
+	}
+}
@@ -0,0 +1,97 @@
+//! Notices shown to users as toasts, which are kept in the log page.
+
+use crate::{format_timestamp, unix_now};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{error, info, warn};
+
+/// How many notices the log keeps, older ones are dropped first.
+pub const LOG_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	Info,
+	Warning,
+	Error,
+}
+
+impl Severity {
+	/// A symbol to tell severities apart in the log.
+	#[must_use]
+	pub fn symbol(self) -> &'static str {
+		match self {
+			Self::Info => "ℹ",
+			Self::Warning => "⚠",
+			Self::Error => "✗",
+		}
+	}
+}
+
+/// Something users should know about, e.g. a dictionary which could not be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notice {
+	/// Unique among notices, ids of newer notices are greater.
+	pub id: u64,
+	pub severity: Severity,
+	/// Localized message, which says what happened and what to do about it.
+	pub summary: String,
+	/// Why it happened, e.g. the error message, which is only shown on demand.
+	pub details: Option<String>,
+	pub timestamp: u64,
+}
+
+impl Notice {
+	#[must_use]
+	pub fn new(severity: Severity, summary: String) -> Self {
+		static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+		Self {
+			id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+			severity,
+			summary,
+			details: None,
+			timestamp: unix_now(),
+		}
+	}
+
+	#[must_use]
+	pub fn info(summary: String) -> Self {
+		Self::new(Severity::Info, summary)
+	}
+
+	#[must_use]
+	pub fn warning(summary: String) -> Self {
+		Self::new(Severity::Warning, summary)
+	}
+
+	#[must_use]
+	pub fn error(summary: String) -> Self {
+		Self::new(Severity::Error, summary)
+	}
+
+	#[must_use]
+	pub fn details(mut self, details: impl Into<String>) -> Self {
+		self.details = Some(details.into());
+		self
+	}
+
+	/// Write the notice to the tracing log too.
+	pub fn trace(&self) {
+		let details = self.details.as_deref().unwrap_or_default();
+		match self.severity {
+			Severity::Info => info!(details, "{}", self.summary),
+			Severity::Warning => warn!(details, "{}", self.summary),
+			Severity::Error => error!(details, "{}", self.summary),
+		}
+	}
+
+	/// The line shown in the log page.
+	#[must_use]
+	pub fn label(&self) -> String {
+		format!(
+			"{} {} — {}",
+			self.severity.symbol(),
+			format_timestamp(self.timestamp),
+			self.summary
+		)
+	}
+}
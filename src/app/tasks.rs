@@ -1,34 +1,320 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
-use super::Message;
-use crate::{Dictionary, import_odict, is_odict_file_compatible, read_odict_file_from_path};
+use super::{
+	FULL_TEXT_LIMIT, FULL_TEXT_PREFIX, Message, PATTERN_LIMIT, RESULTS_PAGE_SIZE, SUGGESTION_LIMIT,
+};
+use crate::{
+	ConflictResolution, DictFileInfo, DictInfo, DictStore, Dictionary, Error, Folding,
+	ImportConflict, ImportReport, ImportStatus, IndexCache, LazyDict, Pattern, SearchCursor,
+	SearchMode, elapsed_secs, fl, import_odict, interleave_rankings, now, rename_odict,
+};
+use cosmic::dialog::file_chooser;
 use cosmic::task;
+use odict::Entry;
+use tracing::{debug, error};
 use url::Url;
 
 type Task = cosmic::Task<cosmic::Action<Message>>;
 
-pub fn create_import_task(url: Url) -> Task {
+/// Import dictionaries from `urls` to `store`, which are files, archives or directories.
+///
+/// Importing reads and writes files, so it's done in a blocking thread.
+pub fn create_import_task(urls: Vec<Url>, store: DictStore) -> Task {
 	task::future(async move {
-		match import_odict(&url).await {
-			Err(err) => Message::ImportError(err.to_string()),
-			Ok((odict, path)) => Message::ODictCopied(odict, path),
+		let import = move || {
+			let mut reports = Vec::new();
+			for url in urls {
+				match import_odict(&url, &store) {
+					Ok(url_reports) => reports.extend(url_reports),
+					Err(err) => {
+						// URLs which are not files are shown as they are
+						let source = url
+							.to_file_path()
+							.unwrap_or_else(|()| PathBuf::from(url.to_string()));
+						reports.push(ImportReport::new(source, Err(err)));
+					}
+				}
+			}
+			reports
+		};
+
+		match tokio::task::spawn_blocking(import).await {
+			Ok(reports) => Message::DictsImported(reports),
+			Err(err) => {
+				error!("import failed: {err}");
+				Message::DictsImported(Vec::new())
+			}
+		}
+	})
+}
+
+/// Resolve the import conflict of the import report at `i` in a blocking thread.
+pub fn create_resolve_task(
+	i: usize,
+	conflict: ImportConflict,
+	resolution: ConflictResolution,
+) -> Task {
+	task::future(async move {
+		let status = match tokio::task::spawn_blocking(move || conflict.resolve(resolution)).await {
+			Ok(Ok(Some(path))) => ImportStatus::Imported(path),
+			Ok(Ok(None)) => ImportStatus::Skipped,
+			Ok(Err(err)) => ImportStatus::Failed(err),
+			Err(err) => ImportStatus::Failed(std::io::Error::other(err).into()),
+		};
+		Message::ConflictResolved(i, status)
+	})
+}
+
+/// Find dictionaries in `store` in a blocking thread, `generation` is given back to
+/// drop results of older scans.
+pub fn create_scan_task(generation: u64, store: DictStore) -> Task {
+	task::future(async move {
+		match tokio::task::spawn_blocking(move || store.dict_paths()).await {
+			Ok(paths) => Message::DictsScanned(generation, paths),
+			Err(err) => {
+				error!("scanning dictionaries failed: {err}");
+				Message::Unhandled
+			}
+		}
+	})
+}
+
+pub fn create_load_task(path: PathBuf, folding: Folding, cache: IndexCache) -> Task {
+	task::future(async move {
+		match Dictionary::load_from_path_cached(&path, folding, &cache) {
+			Ok(dict) => Message::LoadDict((path, dict)),
+			Err(Error::IncompatibleVersion { found, .. }) => {
+				Message::DictNotCompatible((path, found))
+			}
+			Err(err) => Message::LoadError((path, err)),
+		}
+	})
+}
+
+/// Rename the dictionary at `path` to `name`, then load it again.
+pub fn create_rename_task(
+	path: PathBuf,
+	name: String,
+	folding: Folding,
+	cache: IndexCache,
+) -> Task {
+	task::future(async move {
+		let result = rename_odict(&path, &name).and_then(|(old_name, new_path)| {
+			if new_path != path
+				&& let Err(err) = cache.remove(&path)
+			{
+				error!("failed to remove index cache of {path:?}: {err}");
+			}
+			let dict = Dictionary::load_from_path_cached(&new_path, folding, &cache)?;
+			Ok((old_name, new_path, dict))
+		});
+		match result {
+			Ok((old_name, new_path, dict)) => Message::DictRenamed(path, new_path, old_name, dict),
+			Err(err) => Message::RenameError(err.to_string()),
+		}
+	})
+}
+
+/// Read metadata of the dictionary at `path` and count its content, it's loaded first
+/// if `dict` is `None`.
+pub fn create_info_task(
+	path: PathBuf,
+	dict: Option<Dictionary>,
+	folding: Folding,
+	cache: IndexCache,
+) -> Task {
+	task::future(async move {
+		let info = DictFileInfo::read(&path).and_then(|file| {
+			let dict = match dict {
+				Some(dict) => dict,
+				None => Dictionary::load_from_path_cached(&path, folding, &cache)?,
+			};
+			Ok(DictInfo {
+				file,
+				stats: dict.stats(),
+			})
+		});
+		match info {
+			Ok(info) => Message::DictInfoLoaded(info),
+			Err(err) => Message::DictInfoError(err.to_string()),
 		}
 	})
 }
 
-pub fn create_load_task(index: usize, path: PathBuf) -> Task {
+/// Ask for a file to save to, then write `content` to it.
+pub fn create_export_task(file_name: String, content: String) -> Task {
 	task::future(async move {
-		let odict_file = match read_odict_file_from_path(&path) {
-			Ok(file) => file,
-			Err(err) => return Message::LoadError(err.to_string()),
+		let dialog = file_chooser::save::Dialog::new()
+			.title(fl!("export"))
+			.file_name(file_name);
+
+		let url = match dialog.save_file().await {
+			Ok(response) => match response.url() {
+				Some(url) => url.to_owned(),
+				None => return Message::Unhandled,
+			},
+			Err(file_chooser::Error::Cancelled) => return Message::Unhandled,
+			Err(err) => return Message::ExportError(err.to_string()),
+		};
+		let Ok(path) = url.to_file_path() else {
+			return Message::ExportError(fl!("invalid-url", url = url.to_string()));
 		};
-		if !is_odict_file_compatible(&odict_file) {
-			return Message::DictNotCompatible((index, odict_file.version));
+
+		match tokio::fs::write(&path, content).await {
+			Ok(()) => Message::Exported(path),
+			Err(err) => Message::ExportError(err.to_string()),
 		}
-		let dict = Dictionary::load_from_path(&path);
-		match dict {
-			Ok(dict) => Message::LoadDict((index, dict)),
-			Err(err) => Message::LoadError(err.to_string()),
+	})
+}
+
+/// A search to run by [`create_search_task`].
+pub struct SearchRequest {
+	pub query: String,
+	pub mode: SearchMode,
+	/// Loaded dictionaries to search in.
+	pub dicts: Vec<LazyDict>,
+	pub generation: u64,
+	/// Generation of the latest search, this search is skipped if it's outdated.
+	pub latest: Arc<AtomicU64>,
+	/// How long to wait before searching, so that keystrokes typed fast are searched once.
+	pub debounce: Duration,
+}
+
+/// Results of a [`SearchRequest`].
+#[derive(Debug, Clone)]
+pub struct SearchOutput {
+	pub query: String,
+	/// Whether the query is looked up as a term, rather than as a pattern or in
+	/// definitions.
+	pub is_lookup: bool,
+	pub generation: u64,
+	/// The first page of terms starting with the query, all terms matching the
+	/// pattern up to [`PATTERN_LIMIT`], or the best [`FULL_TEXT_LIMIT`] terms whose
+	/// definitions match the query.
+	pub terms: Vec<String>,
+	/// Where to continue for more terms.
+	pub cursor: SearchCursor,
+	/// Entries of the query, or of the first term found otherwise, with names of their
+	/// dictionaries.
+	pub entries: Vec<(String, Entry)>,
+	/// Terms close to the query, only if no entry is found.
+	pub suggestions: Vec<String>,
+	/// Why the query is not valid.
+	pub error: Option<String>,
+}
+
+impl SearchRequest {
+	fn run(self) -> SearchOutput {
+		let t0 = now();
+		let dicts: Vec<&LazyDict> = self.dicts.iter().collect();
+		let mut output = SearchOutput {
+			query: self.query.clone(),
+			is_lookup: false,
+			generation: self.generation,
+			terms: Vec::new(),
+			cursor: SearchCursor::default(),
+			entries: Vec::new(),
+			suggestions: Vec::new(),
+			error: None,
+		};
+
+		let lookup_term = if let Some(text) = self.query.strip_prefix(FULL_TEXT_PREFIX) {
+			output.terms = search_full_text(&dicts, text);
+			output.terms.first().cloned()
+		} else {
+			match Pattern::new(self.mode, &self.query) {
+				Ok(None) => {
+					output.is_lookup = true;
+					output.cursor = SearchCursor::new(self.query.clone());
+					output.terms = output.cursor.next_page(&dicts, RESULTS_PAGE_SIZE);
+					Some(self.query.clone())
+				}
+				Ok(Some(pattern)) => {
+					let mut terms: Vec<String> = dicts
+						.iter()
+						.filter_map(|dict| dict.dictionary())
+						.flat_map(|dict| dict.search_pattern(&pattern, PATTERN_LIMIT))
+						.collect();
+					terms.sort();
+					terms.dedup();
+					terms.truncate(PATTERN_LIMIT);
+					output.terms = terms;
+					output.terms.first().cloned()
+				}
+				Err(err) => {
+					output.error = Some(err.to_string());
+					None
+				}
+			}
+		};
+
+		if let Some(term) = lookup_term {
+			output.entries = dicts
+				.iter()
+				.filter_map(|dict| {
+					let entry = dict.get(&term).ok().flatten()?;
+					Some((dict.name(), entry.clone()))
+				})
+				.collect();
+		}
+
+		if output.entries.is_empty() && output.is_lookup {
+			let mut suggestions = Vec::new();
+			for dict in &dicts {
+				suggestions.extend(
+					dict.suggest(&self.query, SUGGESTION_LIMIT)
+						.unwrap_or_default(),
+				);
+			}
+			let mut seen = HashSet::new();
+			suggestions.retain(|term| seen.insert(term.clone()));
+			suggestions.truncate(SUGGESTION_LIMIT);
+			output.suggestions = suggestions;
+		}
+		debug!(
+			"search \"{}\" in {} dicts finished in {:.3}s",
+			self.query,
+			dicts.len(),
+			elapsed_secs(&t0)
+		);
+
+		output
+	}
+}
+
+/// Returns terms whose definitions, examples or notes match `text` in any of `dicts`,
+/// best first.
+fn search_full_text(dicts: &[&LazyDict], text: &str) -> Vec<String> {
+	let rankings = dicts
+		.iter()
+		.filter_map(|dict| dict.dictionary())
+		.map(|dict| dict.search_full_text(text, FULL_TEXT_LIMIT))
+		.collect();
+
+	interleave_rankings(rankings, FULL_TEXT_LIMIT)
+}
+
+/// Search in a blocking thread, so that typing is not blocked by searching.
+pub fn create_search_task(request: SearchRequest) -> Task {
+	task::future(async move {
+		if !request.debounce.is_zero() {
+			tokio::time::sleep(request.debounce).await;
+		}
+		// another key is typed while waiting
+		if request.latest.load(Ordering::Relaxed) != request.generation {
+			return Message::Unhandled;
+		}
+
+		match tokio::task::spawn_blocking(move || request.run()).await {
+			Ok(output) => Message::SearchResult(output),
+			Err(err) => {
+				error!("search failed: {err}");
+				Message::Unhandled
+			}
 		}
 	})
 }
@@ -0,0 +1,519 @@
+// SPDX-License-Identifier: MIT
+
+//! Headless command line interface, used for scripts and terminals.
+
+use crate::render::{Format, render_entry};
+use crate::server::{DEFAULT_PORT, Server};
+use crate::{DictFileInfo, DictInfo, DictStore, Dictionary, Folding, LazyDict, SearchCursor, json};
+use std::io::{self, Write};
+use std::process::ExitCode;
+use tracing::warn;
+
+pub const USAGE: &str = "\
+Usage: mydict
+       mydict gui [TERM]
+       mydict lookup [--dict NAME] [--markdown | --json] WORD
+       mydict complete [--dict NAME] [--limit N] [--json] PREFIX
+       mydict list-dicts
+       mydict info [--dict NAME] [--json]
+       mydict serve [--bind ADDRESS] [--port PORT]
+       mydict help
+
+Without a command, the GUI is launched. The `gui` command launches it with TERM as
+the initial search term, which may also be given alone if it's not a command.
+
+Options:
+  -d, --dict NAME   only use the dictionary named NAME
+  -m, --markdown    print entries as Markdown instead of plain text
+  -j, --json        print results as JSON, with a schema_version field
+  -n, --limit N     print at most N completions
+  -b, --bind ADDR   serve on ADDR, default to 127.0.0.1
+  -p, --port PORT   serve on PORT, default to 2628
+
+Exit status is 1 if nothing was found, and 2 if the command line is not valid.";
+
+/// Output format of commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Output {
+	#[default]
+	Text,
+	Markdown,
+	Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+	/// Print the entry of a word.
+	Lookup {
+		word: String,
+		dict: Option<String>,
+		output: Output,
+	},
+	/// Print words starting with a prefix, one per line.
+	Complete {
+		prefix: String,
+		dict: Option<String>,
+		limit: Option<usize>,
+		output: Output,
+	},
+	/// Print names and paths of installed dictionaries.
+	ListDicts,
+	/// Print metadata and statistics of installed dictionaries.
+	Info {
+		dict: Option<String>,
+		output: Output,
+	},
+	/// Serve dictionaries over the DICT protocol.
+	Serve {
+		bind: String,
+		port: u16,
+	},
+	Help,
+	/// Launch the GUI, with the initial search term if any.
+	Gui {
+		term: Option<String>,
+	},
+}
+
+/// Options of commands, each command accepts some of them, see [`Command::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Options {
+	dict: Option<String>,
+	output: Output,
+	limit: Option<usize>,
+	bind: String,
+	port: u16,
+	positionals: Vec<String>,
+}
+
+impl Options {
+	/// Parse `args` of `command`, which accepts options of long names in `accepted`.
+	fn parse(command: &str, args: &[String], accepted: &[&str]) -> anyhow::Result<Self> {
+		let mut options = Self {
+			dict: None,
+			output: Output::Text,
+			limit: None,
+			bind: "127.0.0.1".to_string(),
+			port: DEFAULT_PORT,
+			positionals: Vec::new(),
+		};
+		let mut iter = args.iter();
+		while let Some(arg) = iter.next() {
+			let long = match arg.as_str() {
+				"--" => {
+					options.positionals.extend(iter.by_ref().cloned());
+					break;
+				}
+				"-d" | "--dict" => "--dict",
+				"-m" | "--markdown" => "--markdown",
+				"-j" | "--json" => "--json",
+				"-n" | "--limit" => "--limit",
+				"-b" | "--bind" => "--bind",
+				"-p" | "--port" => "--port",
+				option if option.starts_with('-') && option.len() > 1 => {
+					anyhow::bail!("unknown option: {option}")
+				}
+				_ => {
+					options.positionals.push(arg.clone());
+					continue;
+				}
+			};
+			if !accepted.contains(&long) {
+				anyhow::bail!("{command} doesn't accept {arg}");
+			}
+
+			match long {
+				"--markdown" => options.output = Output::Markdown,
+				"--json" => options.output = Output::Json,
+				_ => {
+					let value = iter
+						.next()
+						.ok_or(anyhow::anyhow!("{arg} requires a value"))?;
+					match long {
+						"--dict" => options.dict = Some(value.clone()),
+						"--limit" => {
+							options.limit = Some(
+								value
+									.parse()
+									.map_err(|_| anyhow::anyhow!("invalid limit: {value}"))?,
+							);
+						}
+						"--bind" => options.bind.clone_from(value),
+						_ => {
+							options.port = value
+								.parse()
+								.map_err(|_| anyhow::anyhow!("invalid port: {value}"))?;
+						}
+					}
+				}
+			}
+		}
+
+		Ok(options)
+	}
+
+	/// Returns the only positional argument, which is `what`.
+	fn single_positional(&self, what: &str) -> anyhow::Result<String> {
+		match self.positionals.as_slice() {
+			[value] => Ok(value.clone()),
+			[] => Err(anyhow::anyhow!("missing {what}")),
+			_ => Err(anyhow::anyhow!(
+				"expect one {what}, found {}",
+				self.positionals.len()
+			)),
+		}
+	}
+
+	/// Fails if there is any positional argument.
+	fn no_positionals(&self) -> anyhow::Result<()> {
+		match self.positionals.first() {
+			Some(arg) => Err(anyhow::anyhow!("unexpected argument: {arg}")),
+			None => Ok(()),
+		}
+	}
+}
+
+impl Command {
+	/// Parse command line arguments, excluding the program name.
+	///
+	/// Returns [`Self::Gui`] without a term if `args` is empty, and with the term if
+	/// it's the only argument and not a command, e.g. from a desktop launcher.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if the command is unknown or its arguments are not valid
+	pub fn parse(args: &[String]) -> anyhow::Result<Self> {
+		let Some((command, args)) = args.split_first() else {
+			return Ok(Self::Gui { term: None });
+		};
+		let options = |accepted: &[&str]| Options::parse(command, args, accepted);
+
+		let command = match command.as_str() {
+			"gui" => {
+				let options = options(&[])?;
+				if options.positionals.len() > 1 {
+					anyhow::bail!(
+						"expect at most one term, found {}",
+						options.positionals.len()
+					);
+				}
+				Self::Gui {
+					term: options.positionals.into_iter().next(),
+				}
+			}
+			"lookup" => {
+				let options = options(&["--dict", "--markdown", "--json"])?;
+				Self::Lookup {
+					word: options.single_positional("word")?,
+					dict: options.dict,
+					output: options.output,
+				}
+			}
+			"complete" => {
+				let options = options(&["--dict", "--limit", "--json"])?;
+				Self::Complete {
+					prefix: options.single_positional("prefix")?,
+					dict: options.dict,
+					limit: options.limit,
+					output: options.output,
+				}
+			}
+			"list-dicts" => {
+				options(&[])?.no_positionals()?;
+				Self::ListDicts
+			}
+			"info" => {
+				let options = options(&["--dict", "--json"])?;
+				options.no_positionals()?;
+				Self::Info {
+					dict: options.dict,
+					output: options.output,
+				}
+			}
+			"serve" => {
+				let options = options(&["--bind", "--port"])?;
+				options.no_positionals()?;
+				Self::Serve {
+					bind: options.bind,
+					port: options.port,
+				}
+			}
+			"help" | "-h" | "--help" => Self::Help,
+			option if option.starts_with('-') => anyhow::bail!("unknown option: {option}"),
+			term if args.is_empty() => Self::Gui {
+				term: Some(term.to_string()),
+			},
+			command => anyhow::bail!("unknown command: {command}"),
+		};
+
+		Ok(command)
+	}
+
+	/// Run the command with dictionaries in `store` and return the exit status.
+	///
+	/// [`Self::Gui`] is not run here, but by the binary, which fails with it.
+	#[must_use]
+	pub fn run(self, store: &DictStore) -> ExitCode {
+		let result = match self {
+			Self::Lookup { word, dict, output } => lookup(store, &word, dict.as_deref(), output),
+			Self::Complete {
+				prefix,
+				dict,
+				limit,
+				output,
+			} => complete(store, &prefix, dict.as_deref(), limit, output),
+			Self::ListDicts => list_dicts(store),
+			Self::Info { dict, output } => info(store, dict.as_deref(), output),
+			Self::Serve { bind, port } => serve(store, &bind, port),
+			Self::Help => writeln!(io::stdout(), "{USAGE}")
+				.map(|()| true)
+				.map_err(anyhow::Error::from),
+			// the GUI is launched by the binary, which is built with it
+			Self::Gui { .. } => Err(anyhow::anyhow!("the GUI is not a headless command")),
+		};
+
+		match result {
+			Ok(true) => ExitCode::SUCCESS,
+			Ok(false) => ExitCode::FAILURE,
+			Err(err) => {
+				eprintln!("mydict: {err}");
+				ExitCode::FAILURE
+			}
+		}
+	}
+}
+
+/// Load dictionaries named `name`, or all compatible dictionaries if `name` is `None`.
+fn load_dicts(store: &DictStore, name: Option<&str>) -> anyhow::Result<Vec<LazyDict>> {
+	let mut dicts = store.dicts();
+
+	if let Some(name) = name {
+		dicts.retain(|dict| dict.name() == name);
+		if dicts.is_empty() {
+			anyhow::bail!("dictionary not found: {name}");
+		}
+	}
+
+	let cache = store.index_cache();
+	for dict in &mut dicts {
+		match Dictionary::load_from_path_cached(&dict.path, Folding::default(), &cache) {
+			Ok(dictionary) => dict.load(dictionary),
+			Err(err) if name.is_none() => warn!("skip {:?}: {err}", dict.path),
+			Err(err) => return Err(err.into()),
+		}
+	}
+	dicts.retain(LazyDict::is_loaded);
+
+	Ok(dicts)
+}
+
+fn lookup(
+	store: &DictStore,
+	word: &str,
+	name: Option<&str>,
+	output: Output,
+) -> anyhow::Result<bool> {
+	let dicts = load_dicts(store, name)?;
+	let mut stdout = io::stdout().lock();
+
+	let format = match output {
+		Output::Text => Format::Text,
+		Output::Markdown => Format::Markdown,
+		Output::Json => {
+			let mut lookup = json::Lookup::new(word);
+			for dict in &dicts {
+				if let Some(entry) = dict.get(word)? {
+					lookup.push(dict.name(), entry);
+				}
+			}
+			serde_json::to_writer(&mut stdout, &lookup)?;
+			writeln!(stdout)?;
+
+			return Ok(!lookup.results.is_empty());
+		}
+	};
+
+	let mut found = false;
+
+	for dict in &dicts {
+		let Some(entry) = dict.get(word)? else {
+			continue;
+		};
+
+		if found {
+			writeln!(stdout)?;
+		}
+		// label entries when there may be more than one dictionary
+		if name.is_none() {
+			match format {
+				Format::Text => writeln!(stdout, "[{}]", dict.name())?,
+				Format::Markdown => writeln!(stdout, "> {}\n", dict.name())?,
+			}
+		}
+		writeln!(
+			stdout,
+			"{}",
+			render_entry(entry, format, Some(&dict.name()))
+		)?;
+		found = true;
+	}
+
+	Ok(found)
+}
+
+fn complete(
+	store: &DictStore,
+	prefix: &str,
+	name: Option<&str>,
+	limit: Option<usize>,
+	output: Output,
+) -> anyhow::Result<bool> {
+	let dicts = load_dicts(store, name)?;
+	let dicts: Vec<&LazyDict> = dicts.iter().collect();
+	let terms =
+		SearchCursor::new(prefix.to_string()).next_page(&dicts, limit.unwrap_or(usize::MAX));
+
+	let mut stdout = io::stdout().lock();
+	if output == Output::Json {
+		serde_json::to_writer(&mut stdout, &json::Completion::new(prefix, &terms))?;
+		writeln!(stdout)?;
+	} else {
+		for term in &terms {
+			writeln!(stdout, "{term}")?;
+		}
+	}
+
+	Ok(!terms.is_empty())
+}
+
+fn list_dicts(store: &DictStore) -> anyhow::Result<bool> {
+	let dicts = store.dicts();
+	let mut stdout = io::stdout().lock();
+	for dict in &dicts {
+		writeln!(stdout, "{}\t{}", dict.name(), dict.path.display())?;
+	}
+
+	Ok(!dicts.is_empty())
+}
+
+fn info(store: &DictStore, name: Option<&str>, output: Output) -> anyhow::Result<bool> {
+	let dicts = load_dicts(store, name)?;
+	let mut infos = Vec::new();
+	for dict in &dicts {
+		if let Some(dictionary) = dict.dictionary() {
+			infos.push(DictInfo {
+				file: DictFileInfo::read(&dict.path)?,
+				stats: dictionary.stats(),
+			});
+		}
+	}
+
+	let mut stdout = io::stdout().lock();
+	if output == Output::Json {
+		serde_json::to_writer(&mut stdout, &json::Info::new(&infos))?;
+		writeln!(stdout)?;
+	} else {
+		for (i, info) in infos.iter().enumerate() {
+			if i > 0 {
+				writeln!(stdout)?;
+			}
+			for (field, value) in info.fields() {
+				writeln!(stdout, "{}: {value}", field.label())?;
+			}
+		}
+	}
+
+	Ok(!infos.is_empty())
+}
+
+fn serve(store: &DictStore, bind: &str, port: u16) -> anyhow::Result<bool> {
+	Server::new(store.dicts(), store.index_cache()).listen((bind, port))?;
+
+	Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(args: &[&str]) -> anyhow::Result<Command> {
+		let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+		Command::parse(&args)
+	}
+
+	#[test]
+	fn parse_gui() {
+		assert_eq!(parse(&[]).unwrap(), Command::Gui { term: None });
+		assert_eq!(
+			parse(&["gui", "run"]).unwrap(),
+			Command::Gui {
+				term: Some("run".to_string())
+			}
+		);
+		assert!(parse(&["gui", "run", "walk"]).is_err());
+	}
+
+	#[test]
+	fn parse_bare_term() {
+		for term in ["receive", "ice cream", "/tmp/notes.txt"] {
+			assert_eq!(
+				parse(&[term]).unwrap(),
+				Command::Gui {
+					term: Some(term.to_string())
+				}
+			);
+		}
+		// commands and options are never terms
+		assert!(parse(&["lookup"]).is_err());
+		assert!(parse(&["--verbose"]).is_err());
+		assert_eq!(parse(&["help"]).unwrap(), Command::Help);
+	}
+
+	#[test]
+	fn parse_commands() {
+		assert_eq!(
+			parse(&["lookup", "--json", "-d", "English", "run"]).unwrap(),
+			Command::Lookup {
+				word: "run".to_string(),
+				dict: Some("English".to_string()),
+				output: Output::Json,
+			}
+		);
+		assert_eq!(
+			parse(&["complete", "-n", "10", "--", "-re"]).unwrap(),
+			Command::Complete {
+				prefix: "-re".to_string(),
+				dict: None,
+				limit: Some(10),
+				output: Output::Text,
+			}
+		);
+		assert_eq!(parse(&["list-dicts"]).unwrap(), Command::ListDicts);
+		assert_eq!(
+			parse(&["serve", "--port", "2629"]).unwrap(),
+			Command::Serve {
+				bind: "127.0.0.1".to_string(),
+				port: 2629,
+			}
+		);
+		assert_eq!(parse(&["--help"]).unwrap(), Command::Help);
+	}
+
+	#[test]
+	fn parse_invalid() {
+		for args in [
+			&["run", "walk"][..],
+			&["--json", "lookup", "run"],
+			&["lookup"],
+			&["lookup", "run", "walk"],
+			&["lookup", "--limit", "10", "run"],
+			&["lookup", "--dict"],
+			&["lookup", "--verbose", "run"],
+			&["complete", "-n", "ten", "re"],
+			&["list-dicts", "extra"],
+			&["serve", "--port", "http"],
+		] {
+			assert!(parse(args).is_err(), "{args:?}");
+		}
+	}
+}
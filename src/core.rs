@@ -1,8 +1,12 @@
-pub mod config;
 pub mod dict;
-pub mod font;
+pub mod history;
+#[cfg(feature = "gui")]
 pub mod i18n;
+pub mod json;
+pub mod link;
+pub mod render;
 pub mod utils;
+pub mod wordlist;
 
 pub use dict::*;
 pub use utils::*;
@@ -1,10 +0,0 @@
-// SPDX-License-Identifier: MIT
-
-use cosmic::cosmic_config::{self, CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry};
-
-#[derive(Debug, Default, Clone, CosmicConfigEntry, Eq, PartialEq)]
-#[version = 1]
-pub struct Config {
-	pub selected_index: usize,
-	pub search_term: String,
-}
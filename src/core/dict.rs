@@ -1,9 +1,29 @@
+pub mod cache;
+pub mod cursor;
 pub mod dictionary;
+pub mod error;
+pub mod fold;
+pub mod fulltext;
+pub mod import;
+pub mod index;
 pub mod lazydict;
 pub mod odict;
-pub mod trie;
+pub mod pattern;
+pub mod stardict;
+pub mod stats;
+pub mod store;
 
+pub use cache::*;
+pub use cursor::*;
 pub use dictionary::*;
+pub use error::*;
+pub use fold::*;
+pub use fulltext::*;
+pub use import::*;
+pub use index::*;
 pub use lazydict::*;
 pub use odict::*;
-pub use trie::*;
+pub use pattern::*;
+pub use stardict::*;
+pub use stats::*;
+pub use store::*;
@@ -0,0 +1,481 @@
+//! On-disk cache of search indexes, so that large dictionaries don't need to be indexed
+//! again on every startup.
+//!
+//! A cache file starts with a header describing the dictionary file it was built from,
+//! i.e. its path, size, modification time and content hash. The cache is only used
+//! when all of them still match, so it's invalidated as soon as the file changes.
+//!
+//! The term index and the full-text index are cached in separate files, since the
+//! latter is only built on demand.
+
+use super::{Error, Folding, FullTextIndex, Result, TermIndex};
+use crate::write_atomically;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::{info, warn};
+
+const MAGIC: &[u8; 8] = b"MYDICTIX";
+/// Bump this whenever the layout of cache files, the folding of keys or the
+/// tokenizing of full text changes.
+const CACHE_VERSION: u32 = 4;
+/// Extension of term index cache files.
+const INDEX: &str = "index";
+/// Extension of full-text index cache files.
+const FULL_TEXT: &str = "fulltext";
+
+/// The index of a dictionary, see [`Dictionary`](super::Dictionary).
+pub(crate) struct Index {
+	pub terms: TermIndex,
+	pub originals: HashMap<usize, Vec<String>>,
+}
+
+/// Identifies the state of a dictionary file the index is built from.
+#[derive(Debug, PartialEq, Eq)]
+struct Key {
+	/// Encoded bytes of the path, see [`std::ffi::OsStr::as_encoded_bytes`].
+	path: Vec<u8>,
+	size: u64,
+	mtime_secs: u64,
+	mtime_nanos: u32,
+	hash: u64,
+	strip_accents: bool,
+}
+
+impl Key {
+	/// Returns the key of the file at `path`, the content is only hashed when
+	/// `expected` has the same size and modification time, since that's expensive.
+	fn of(path: &Path, folding: Folding, expected: Option<&Self>) -> io::Result<Self> {
+		let metadata = fs::metadata(path)?;
+		let mtime = metadata
+			.modified()?
+			.duration_since(UNIX_EPOCH)
+			.unwrap_or_default();
+		let mut key = Self {
+			path: path.as_os_str().as_encoded_bytes().to_vec(),
+			size: metadata.len(),
+			mtime_secs: mtime.as_secs(),
+			mtime_nanos: mtime.subsec_nanos(),
+			hash: 0,
+			strip_accents: folding.strip_accents,
+		};
+
+		let same_stat = expected.is_none_or(|expected| {
+			expected.size == key.size
+				&& expected.mtime_secs == key.mtime_secs
+				&& expected.mtime_nanos == key.mtime_nanos
+		});
+		if same_stat {
+			key.hash = hash_file(path)?;
+		}
+
+		Ok(key)
+	}
+
+	fn write_to(&self, buf: &mut Vec<u8>) {
+		put_bytes(buf, &self.path);
+		buf.extend_from_slice(&self.size.to_le_bytes());
+		buf.extend_from_slice(&self.mtime_secs.to_le_bytes());
+		buf.extend_from_slice(&self.mtime_nanos.to_le_bytes());
+		buf.extend_from_slice(&self.hash.to_le_bytes());
+		buf.push(u8::from(self.strip_accents));
+	}
+
+	fn read_from(bytes: &mut &[u8]) -> Result<Self> {
+		Ok(Self {
+			path: take_bytes(bytes)?.to_vec(),
+			size: take_u64(bytes)?,
+			mtime_secs: take_u64(bytes)?,
+			mtime_nanos: take_u32(bytes)?,
+			hash: take_u64(bytes)?,
+			strip_accents: take_u8(bytes)? != 0,
+		})
+	}
+}
+
+/// A directory of cached indexes, one file per dictionary.
+#[derive(Debug, Clone)]
+pub struct IndexCache {
+	dir: PathBuf,
+}
+
+impl IndexCache {
+	#[must_use]
+	pub fn new(dir: PathBuf) -> Self {
+		Self { dir }
+	}
+
+	/// Returns the cached index of the dictionary at `path`, `None` if there is no
+	/// cache or it's outdated.
+	pub(crate) fn load(&self, path: &Path, folding: Folding) -> Option<Index> {
+		self.read(path, folding, INDEX, read_index)
+	}
+
+	/// Returns the cached full-text index of the dictionary at `path`, `None` if there
+	/// is no cache or it's outdated.
+	pub(crate) fn load_full_text(&self, path: &Path, folding: Folding) -> Option<FullTextIndex> {
+		self.read(path, folding, FULL_TEXT, FullTextIndex::read_from)
+	}
+
+	/// Read the cache file of `kind`, whose body is parsed by `parse_body`.
+	fn read<T>(
+		&self,
+		path: &Path,
+		folding: Folding,
+		kind: &str,
+		parse_body: impl FnOnce(&mut &[u8]) -> Result<T>,
+	) -> Option<T> {
+		let cache_path = self.cache_path(path, kind);
+		let bytes = fs::read(&cache_path).ok()?;
+
+		match Self::parse(&bytes, path, folding, parse_body) {
+			Ok(Some(value)) => {
+				info!("load {kind} of {:?} from {:?}", path, cache_path);
+				Some(value)
+			}
+			Ok(None) => {
+				info!("{kind} cache of {:?} is outdated", path);
+				None
+			}
+			Err(err) => {
+				warn!("failed to read {kind} cache {:?}: {err}", cache_path);
+				None
+			}
+		}
+	}
+
+	/// Returns `None` if the header doesn't match the dictionary file.
+	fn parse<T>(
+		mut bytes: &[u8],
+		path: &Path,
+		folding: Folding,
+		parse_body: impl FnOnce(&mut &[u8]) -> Result<T>,
+	) -> Result<Option<T>> {
+		let bytes = &mut bytes;
+		if take(bytes, MAGIC.len())? != MAGIC || take_u32(bytes)? != CACHE_VERSION {
+			return Ok(None);
+		}
+
+		let cached = Key::read_from(bytes)?;
+		if cached != Key::of(path, folding, Some(&cached))? {
+			return Ok(None);
+		}
+
+		parse_body(bytes).map(Some)
+	}
+
+	/// Write `index` of the dictionary at `path` to the cache.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if the dictionary file could not be read or the cache could
+	/// not be written
+	pub(crate) fn store(&self, path: &Path, folding: Folding, index: &Index) -> Result<()> {
+		self.write(path, folding, INDEX, |buf| {
+			index.terms.write_to(buf);
+			put_u32(buf, index.originals.len())?;
+			for (ordinal, originals) in &index.originals {
+				put_u32(buf, *ordinal)?;
+				put_u32(buf, originals.len())?;
+				for term in originals {
+					put_bytes(buf, term.as_bytes());
+				}
+			}
+			Ok(())
+		})
+	}
+
+	/// Write the full-text `index` of the dictionary at `path` to the cache.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if the dictionary file could not be read or the cache could
+	/// not be written
+	pub(crate) fn store_full_text(
+		&self,
+		path: &Path,
+		folding: Folding,
+		index: &FullTextIndex,
+	) -> Result<()> {
+		self.write(path, folding, FULL_TEXT, |buf| index.write_to(buf))
+	}
+
+	/// Write the cache file of `kind`, whose body is written by `write_body`.
+	fn write(
+		&self,
+		path: &Path,
+		folding: Folding,
+		kind: &str,
+		write_body: impl FnOnce(&mut Vec<u8>) -> Result<()>,
+	) -> Result<()> {
+		let mut buf = Vec::new();
+		buf.extend_from_slice(MAGIC);
+		buf.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+		Key::of(path, folding, None)?.write_to(&mut buf);
+		write_body(&mut buf)?;
+
+		let cache_path = self.cache_path(path, kind);
+		write_atomically(&cache_path, &buf)?;
+		info!("write {kind} of {:?} to {:?}", path, cache_path);
+
+		Ok(())
+	}
+
+	/// Remove all cached indexes of the dictionary at `path`.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if any cache file exists but could not be removed
+	pub fn remove(&self, path: &Path) -> io::Result<()> {
+		for kind in [INDEX, FULL_TEXT] {
+			match fs::remove_file(self.cache_path(path, kind)) {
+				Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+				_ => (),
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Cache files are named after the hash of the dictionary path, with `kind` as the
+	/// extension.
+	fn cache_path(&self, path: &Path, kind: &str) -> PathBuf {
+		let mut hasher = Fnv::default();
+		hasher.write(path.as_os_str().as_encoded_bytes());
+		self.dir.join(format!("{:016x}.{kind}", hasher.finish()))
+	}
+}
+
+/// Read the body of a term index cache file written by [`IndexCache::store`].
+fn read_index(bytes: &mut &[u8]) -> Result<Index> {
+	let terms = TermIndex::read_from(bytes)?;
+	let count = take_u32(bytes)?;
+	// every item takes at least 8 bytes, don't trust the count of a corrupted file
+	let mut originals = HashMap::with_capacity((count as usize).min(bytes.len() / 8));
+	for _ in 0..count {
+		let ordinal = take_u32(bytes)? as usize;
+		let n = take_u32(bytes)?;
+		let terms = (0..n).map(|_| take_str(bytes)).collect::<Result<_>>()?;
+		originals.insert(ordinal, terms);
+	}
+
+	Ok(Index { terms, originals })
+}
+
+/// The FNV-1a hash, which unlike the std hasher is stable across Rust releases.
+struct Fnv(u64);
+
+impl Default for Fnv {
+	fn default() -> Self {
+		Self(0xcbf2_9ce4_8422_2325)
+	}
+}
+
+impl Hasher for Fnv {
+	fn finish(&self) -> u64 {
+		self.0
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 ^= u64::from(*byte);
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+}
+
+/// Hash the content of the file at `path` with [`Fnv`].
+pub(super) fn hash_file(path: &Path) -> io::Result<u64> {
+	let mut file = File::open(path)?;
+	let mut hasher = Fnv::default();
+	let mut buf = vec![0; 64 * 1024];
+	loop {
+		let n = file.read(&mut buf)?;
+		if n == 0 {
+			break;
+		}
+		hasher.write(&buf[..n]);
+	}
+
+	Ok(hasher.finish())
+}
+
+pub(super) fn put_u32(buf: &mut Vec<u8>, n: usize) -> Result<()> {
+	buf.extend_from_slice(&u32::try_from(n)?.to_le_bytes());
+	Ok(())
+}
+
+/// Write `bytes` prefixed by its length.
+pub(super) fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+	// lengths of terms and paths never get close to 4 GiB
+	buf.extend_from_slice(&u32::try_from(bytes.len()).unwrap_or(u32::MAX).to_le_bytes());
+	buf.extend_from_slice(bytes);
+}
+
+/// Write `ns` prefixed by its length.
+pub(super) fn put_u32s(buf: &mut Vec<u8>, ns: &[u32]) {
+	buf.extend_from_slice(&u32::try_from(ns.len()).unwrap_or(u32::MAX).to_le_bytes());
+	for n in ns {
+		buf.extend_from_slice(&n.to_le_bytes());
+	}
+}
+
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+	if bytes.len() < n {
+		return Err(Error::format("unexpected end of index cache"));
+	}
+	let (head, tail) = bytes.split_at(n);
+	*bytes = tail;
+
+	Ok(head)
+}
+
+fn take_u8(bytes: &mut &[u8]) -> Result<u8> {
+	Ok(take(bytes, 1)?[0])
+}
+
+pub(super) fn take_u32(bytes: &mut &[u8]) -> Result<u32> {
+	Ok(u32::from_le_bytes(take(bytes, 4)?.try_into()?))
+}
+
+fn take_u64(bytes: &mut &[u8]) -> Result<u64> {
+	Ok(u64::from_le_bytes(take(bytes, 8)?.try_into()?))
+}
+
+pub(super) fn take_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8]> {
+	let n = take_u32(bytes)? as usize;
+	take(bytes, n)
+}
+
+pub(super) fn take_str(bytes: &mut &[u8]) -> Result<String> {
+	Ok(String::from_utf8(take_bytes(bytes)?.to_vec())?)
+}
+
+/// Read `u32`s written by [`put_u32s`].
+pub(super) fn take_u32s(bytes: &mut &[u8]) -> Result<Vec<u32>> {
+	let n = take_u32(bytes)? as usize;
+	Ok(take(bytes, n.saturating_mul(4))?
+		.chunks_exact(4)
+		.map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+		.collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::TempDir;
+
+	/// A cache with the index of a dictionary file in a temporary directory.
+	struct Fixture {
+		_dir: TempDir,
+		dict_path: PathBuf,
+		cache: IndexCache,
+	}
+
+	impl Fixture {
+		fn new(name: &str) -> Self {
+			let dir = TempDir::new(&format!("cache-{name}"));
+			let dict_path = dir.path().join("dict.odict");
+			fs::write(&dict_path, "dictionary content").unwrap();
+			let cache = IndexCache::new(dir.path().join("cache"));
+
+			let index = Index {
+				terms: TermIndex::from_sorted(["cafe", "run", "walk"]),
+				originals: HashMap::from([(0, vec!["café".to_string()])]),
+			};
+			cache.store(&dict_path, Folding::default(), &index).unwrap();
+
+			Self {
+				_dir: dir,
+				dict_path,
+				cache,
+			}
+		}
+
+		fn cache_path(&self) -> PathBuf {
+			self.cache.cache_path(&self.dict_path, INDEX)
+		}
+
+		fn parse(&self, bytes: &[u8]) -> Result<Option<Index>> {
+			IndexCache::parse(bytes, &self.dict_path, Folding::default(), read_index)
+		}
+	}
+
+	#[test]
+	fn load_stored() {
+		let fixture = Fixture::new("stored");
+
+		let index = fixture
+			.cache
+			.load(&fixture.dict_path, Folding::default())
+			.unwrap();
+		assert_eq!(
+			index.terms.iter().collect::<Vec<_>>(),
+			["cafe", "run", "walk"]
+		);
+		assert_eq!(index.originals[&0], ["café"]);
+		assert!(
+			fixture
+				.cache
+				.load(&fixture.dict_path, Folding::new(false))
+				.is_none()
+		);
+	}
+
+	#[test]
+	fn load_truncated() {
+		let fixture = Fixture::new("truncated");
+		let bytes = fs::read(fixture.cache_path()).unwrap();
+
+		for len in 0..bytes.len() {
+			let result = fixture.parse(&bytes[..len]);
+			assert!(matches!(result, Err(Error::Format(_))), "{len}");
+		}
+
+		fs::write(fixture.cache_path(), &bytes[..bytes.len() - 1]).unwrap();
+		assert!(
+			fixture
+				.cache
+				.load(&fixture.dict_path, Folding::default())
+				.is_none()
+		);
+	}
+
+	#[test]
+	fn load_garbage() {
+		let fixture = Fixture::new("garbage");
+		let bytes = fs::read(fixture.cache_path()).unwrap();
+		// magic, version and the key, see `Key::write_to`
+		let header = MAGIC.len() + 4 + 4 + fixture.dict_path.as_os_str().len() + 8 + 8 + 4 + 8 + 1;
+		assert!(matches!(
+			fixture.parse(&bytes[..header]),
+			Err(Error::Format(_))
+		));
+
+		for garbage in [vec![0; 16], vec![0xff; 16], b"not an index".to_vec()] {
+			let mut corrupted = bytes[..header].to_vec();
+			corrupted.extend_from_slice(&garbage);
+			let result = fixture.parse(&corrupted);
+			assert!(matches!(result, Err(Error::Format(_))), "{garbage:?}");
+
+			fs::write(fixture.cache_path(), &corrupted).unwrap();
+			assert!(
+				fixture
+					.cache
+					.load(&fixture.dict_path, Folding::default())
+					.is_none()
+			);
+		}
+
+		fs::write(fixture.cache_path(), b"garbage").unwrap();
+		assert!(
+			fixture
+				.cache
+				.load(&fixture.dict_path, Folding::default())
+				.is_none()
+		);
+	}
+}
@@ -0,0 +1,176 @@
+use super::LazyDict;
+
+/// Paginated prefix search in several dictionaries, whose results are merged in order
+/// without duplicates.
+#[derive(Debug, Clone)]
+pub struct SearchCursor {
+	query: String,
+	/// Number of results consumed from each dictionary.
+	offsets: Vec<usize>,
+	exhausted: bool,
+}
+
+/// A cursor without any results.
+impl Default for SearchCursor {
+	fn default() -> Self {
+		Self {
+			query: String::new(),
+			offsets: Vec::new(),
+			exhausted: true,
+		}
+	}
+}
+
+impl SearchCursor {
+	#[must_use]
+	pub fn new(query: String) -> Self {
+		Self {
+			query,
+			offsets: Vec::new(),
+			exhausted: false,
+		}
+	}
+
+	#[must_use]
+	pub fn query(&self) -> &str {
+		&self.query
+	}
+
+	/// Whether all results are returned.
+	#[must_use]
+	pub fn is_exhausted(&self) -> bool {
+		self.exhausted
+	}
+
+	/// Returns the next at most `limit` results in `dicts`, which should be the same
+	/// dictionaries in the same order on every call. Dictionaries not loaded are skipped.
+	pub fn next_page(&mut self, dicts: &[&LazyDict], limit: usize) -> Vec<String> {
+		if self.exhausted {
+			return Vec::new();
+		}
+		self.offsets.resize(dicts.len(), 0);
+
+		// results are ordered by folded terms first, which is the order of each dictionary
+		let query = &self.query;
+		let mut iters: Vec<_> = dicts
+			.iter()
+			.zip(self.offsets.clone())
+			.map(|(dict, offset)| {
+				dict.dictionary()
+					.into_iter()
+					.flat_map(move |dict| {
+						dict.matches(query)
+							.skip(offset)
+							.map(|term| (dict.fold(&term), term))
+					})
+					.peekable()
+			})
+			.collect();
+
+		let mut result = Vec::new();
+		while result.len() < limit {
+			let Some(min) = iters
+				.iter_mut()
+				.filter_map(|iter| iter.peek())
+				.min()
+				.cloned()
+			else {
+				self.exhausted = true;
+				break;
+			};
+
+			for (iter, offset) in iters.iter_mut().zip(&mut self.offsets) {
+				if iter.next_if_eq(&min).is_some() {
+					*offset += 1;
+				}
+			}
+			result.push(min.1);
+		}
+
+		result
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Dictionary;
+	use std::path::PathBuf;
+
+	fn loaded(terms: &[&str]) -> LazyDict {
+		let mut odict = odict::Dictionary::default();
+		for term in terms {
+			odict.entries.insert(odict::Entry {
+				term: (*term).to_string(),
+				..Default::default()
+			});
+		}
+		let mut dict = LazyDict::new(PathBuf::from("test.odict"));
+		dict.load(Dictionary::new(odict));
+
+		dict
+	}
+
+	/// Returns all pages of `query` in `dicts`.
+	fn pages(dicts: &[&LazyDict], query: &str, limit: usize) -> Vec<Vec<String>> {
+		let mut cursor = SearchCursor::new(query.to_string());
+		let mut pages = Vec::new();
+		while !cursor.is_exhausted() {
+			pages.push(cursor.next_page(dicts, limit));
+		}
+
+		pages
+	}
+
+	#[test]
+	fn merge_in_order() {
+		let english = loaded(&["rain", "ran", "run", "rune"]);
+		let french = loaded(&["Rue", "rêve", "run"]);
+		let dicts = [&english, &french];
+
+		// merged by folded terms, duplicates across dictionaries are returned once
+		let mut cursor = SearchCursor::new("r".to_string());
+		assert_eq!(
+			cursor.next_page(&dicts, 100),
+			["rain", "ran", "rêve", "Rue", "run", "rune"]
+		);
+		assert!(cursor.is_exhausted());
+		assert!(cursor.next_page(&dicts, 100).is_empty());
+	}
+
+	#[test]
+	fn resume_pages() {
+		let english = loaded(&["rain", "ran", "run", "rune"]);
+		let french = loaded(&["Rue", "rêve", "run"]);
+		let dicts = [&english, &french];
+
+		let mut cursor = SearchCursor::new("r".to_string());
+		assert_eq!(cursor.next_page(&dicts, 2), ["rain", "ran"]);
+		assert_eq!(cursor.next_page(&dicts, 3), ["rêve", "Rue", "run"]);
+		assert!(!cursor.is_exhausted());
+
+		for limit in 1..=7 {
+			let pages = pages(&dicts, "r", limit);
+			assert!(pages.iter().rev().skip(1).all(|page| page.len() == limit));
+			assert_eq!(
+				pages.concat(),
+				["rain", "ran", "rêve", "Rue", "run", "rune"],
+				"{limit}"
+			);
+		}
+	}
+
+	#[test]
+	fn skip_unloaded() {
+		let english = loaded(&["run", "walk"]);
+		let unloaded = LazyDict::new(PathBuf::from("unloaded.odict"));
+
+		assert_eq!(pages(&[&unloaded, &english], "", 10), [["run", "walk"]]);
+		assert!(pages(&[&english], "x", 10).concat().is_empty());
+		assert!(
+			SearchCursor::default()
+				.next_page(&[&english], 10)
+				.is_empty()
+		);
+	}
+}
@@ -1,43 +1,354 @@
-use super::{Trie, read_odict_from_path};
+use super::{
+	DictStats, Folding, FullTextIndex, Glob, Index, IndexCache, IndexTiming, Pattern, Result,
+	TermIndex, Terms, read_odict_from_path,
+};
 use crate::{elapsed_secs, now};
-use std::path::Path;
-use tracing::info;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tracing::{info, warn};
 
 /// Not useful on its own, you should use the `LazyDict`.
+///
+/// Cloning a dictionary is cheap, since its data is shared.
 #[derive(Debug, Clone)]
 pub struct Dictionary {
-	pub(crate) odict: odict::Dictionary,
-	pub(crate) trie: Trie,
+	pub(crate) odict: Arc<odict::Dictionary>,
+	/// Index of folded terms.
+	pub(crate) index: TermIndex,
+	pub(crate) folding: Folding,
+	/// Maps ordinals of folded terms back to the original terms, terms which are
+	/// the only original of themselves are left out to save memory.
+	pub(crate) originals: Arc<HashMap<usize, Vec<String>>>,
+	/// Built on first use, and shared by clones.
+	pub(crate) full_text: Arc<LazyFullText>,
+	pub(crate) index_timing: IndexTiming,
+}
+
+/// A full-text index built when it's first used.
+#[derive(Debug)]
+pub(crate) struct LazyFullText {
+	index: OnceLock<FullTextIndex>,
+	/// The dictionary file and where to cache the index, if the dictionary is loaded
+	/// with a cache.
+	cache: Option<(PathBuf, IndexCache)>,
 }
 
 impl Dictionary {
 	pub fn new(odict: odict::Dictionary) -> Self {
+		Self::with_folding(odict, Folding::default())
+	}
+
+	pub fn with_folding(odict: odict::Dictionary, folding: Folding) -> Self {
+		let t0 = now();
+		let index = Self::build_index(&odict, folding);
+		let timing = IndexTiming {
+			secs: elapsed_secs(&t0),
+			cached: false,
+		};
+		Self::with_index(odict, folding, index, timing, None)
+	}
+
+	fn with_index(
+		odict: odict::Dictionary,
+		folding: Folding,
+		index: Index,
+		index_timing: IndexTiming,
+		cache: Option<(PathBuf, IndexCache)>,
+	) -> Self {
+		Self {
+			odict: Arc::new(odict),
+			index: index.terms,
+			folding,
+			originals: Arc::new(index.originals),
+			full_text: Arc::new(LazyFullText {
+				index: OnceLock::new(),
+				cache,
+			}),
+			index_timing,
+		}
+	}
+
+	fn build_index(odict: &odict::Dictionary, folding: Folding) -> Index {
 		let t0 = now();
-		let mut trie = Trie::new();
+		let mut terms: BTreeMap<String, Vec<String>> = BTreeMap::new();
 		for entry in &odict.entries {
-			trie.insert(&entry.term);
+			terms
+				.entry(folding.fold(&entry.term))
+				.or_default()
+				.push(entry.term.clone());
 		}
+
+		let index = TermIndex::from_sorted(terms.keys());
+		let originals = terms
+			.into_iter()
+			.enumerate()
+			.filter_map(|(ordinal, (key, mut originals))| {
+				if originals.len() == 1 && originals[0] == key {
+					return None;
+				}
+				originals.sort();
+				Some((ordinal, originals))
+			})
+			.collect();
 		tracing::info!(
-			"build trie for {} in {:.3}s",
+			"build index for {} in {:.3}s",
 			odict
 				.name
 				.as_ref()
 				.map_or("unknown".to_string(), Clone::clone),
 			elapsed_secs(&t0)
 		);
-		Self { odict, trie }
+
+		Index {
+			terms: index,
+			originals,
+		}
+	}
+
+	/// # Errors
+	///
+	/// Will return `Err` if `path` or the file is not valid
+	pub fn load_from_path(path: &Path) -> Result<Self> {
+		Self::load_from_path_with_folding(path, Folding::default())
 	}
 
 	/// # Errors
 	///
 	/// Will return `Err` if `path` or the file is not valid
-	pub fn load_from_path(path: &Path) -> anyhow::Result<Self> {
+	pub fn load_from_path_with_folding(path: &Path, folding: Folding) -> Result<Self> {
+		let t0 = now();
+		let dict = Self::with_folding(read_odict_from_path(path)?, folding);
+		info!("load {:?} in {:.3}s", path, elapsed_secs(&t0));
+
+		Ok(dict)
+	}
+
+	/// Like [`Self::load_from_path_with_folding`], but the index is read from `cache`
+	/// if it's up to date, otherwise it's built and written to `cache`.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if `path` or the file is not valid, failing to write the
+	/// cache is not an error
+	pub fn load_from_path_cached(
+		path: &Path,
+		folding: Folding,
+		cache: &IndexCache,
+	) -> Result<Self> {
 		let t0 = now();
-		let dict = read_odict_from_path(path)?.into();
+		let odict = read_odict_from_path(path)?;
+		let t1 = now();
+		let (index, cached) = cache.load(path, folding).map_or_else(
+			|| (Self::build_index(&odict, folding), false),
+			|index| (index, true),
+		);
+		let timing = IndexTiming {
+			secs: elapsed_secs(&t1),
+			cached,
+		};
+		if !cached && let Err(err) = cache.store(path, folding, &index) {
+			warn!("failed to cache index of {:?}: {err}", path);
+		}
+		let dict = Self::with_index(
+			odict,
+			folding,
+			index,
+			timing,
+			Some((path.into(), cache.clone())),
+		);
 		info!("load {:?} in {:.3}s", path, elapsed_secs(&t0));
 
 		Ok(dict)
 	}
+
+	/// How terms are folded in the index of the dictionary.
+	#[must_use]
+	pub fn folding(&self) -> Folding {
+		self.folding
+	}
+
+	/// Returns a lazy iterator over original terms starting with `s` after folding.
+	///
+	/// Terms are ordered by their folded terms, and then by themselves.
+	#[must_use]
+	pub fn matches(&self, s: &str) -> Matches<'_> {
+		Matches {
+			dict: self,
+			terms: self.index.prefix(&self.folding.fold(s)),
+			pending: Vec::new(),
+		}
+	}
+
+	/// Returns at most `limit` original terms starting with `s` after folding,
+	/// skipping the first `offset` ones.
+	#[must_use]
+	pub fn search(&self, s: &str, offset: usize, limit: usize) -> Vec<String> {
+		self.matches(s).skip(offset).take(limit).collect()
+	}
+
+	/// Returns at most `limit` original terms matching `pattern`.
+	#[must_use]
+	pub fn search_pattern(&self, pattern: &Pattern, limit: usize) -> Vec<String> {
+		let mut result = Vec::new();
+		match pattern {
+			Pattern::Glob(glob) => {
+				let glob = Glob::new(&self.folding.fold(glob));
+				for (ordinal, key) in self.index.search(&glob, limit) {
+					self.push_originals(&mut result, ordinal, key);
+				}
+			}
+			Pattern::Regex { regex, prefix } => {
+				result.extend(
+					self.matches(prefix)
+						.filter(|term| regex.is_match(term))
+						.take(limit),
+				);
+			}
+		}
+		result.truncate(limit);
+
+		result
+	}
+
+	/// Returns at most `limit` terms whose definitions, examples or notes contain words
+	/// of `query`, with their scores, best first.
+	///
+	/// The full-text index is built on the first call, which is slow for large
+	/// dictionaries, unless it's cached.
+	#[must_use]
+	pub fn search_full_text(&self, query: &str, limit: usize) -> Vec<(String, f64)> {
+		self.full_text_index().search(self.folding, query, limit)
+	}
+
+	fn full_text_index(&self) -> &FullTextIndex {
+		let LazyFullText { index, cache } = self.full_text.as_ref();
+		index.get_or_init(|| {
+			let Some((path, cache)) = cache else {
+				return FullTextIndex::build(&self.odict, self.folding);
+			};
+			cache.load_full_text(path, self.folding).unwrap_or_else(|| {
+				let index = FullTextIndex::build(&self.odict, self.folding);
+				if let Err(err) = cache.store_full_text(path, self.folding, &index) {
+					warn!("failed to cache full-text index of {:?}: {err}", path);
+				}
+				index
+			})
+		})
+	}
+
+	/// Returns statistics of the content, which are counted on every call.
+	#[must_use]
+	pub fn stats(&self) -> DictStats {
+		DictStats::new(&self.odict, self.index_timing)
+	}
+
+	/// Fold `s` like the index does.
+	#[must_use]
+	pub fn fold(&self, s: &str) -> String {
+		self.folding.fold(s)
+	}
+
+	/// Returns at most `limit` original terms close to `s`, nearest first.
+	#[must_use]
+	pub fn suggest(&self, s: &str, max_distance: usize, limit: usize) -> Vec<String> {
+		let mut result = Vec::new();
+		for (key, _) in self
+			.index
+			.suggest(&self.folding.fold(s), max_distance, limit)
+		{
+			if let Some(ordinal) = self.index.ordinal(&key) {
+				self.push_originals(&mut result, ordinal, key);
+			}
+		}
+		result.truncate(limit);
+		result
+	}
+
+	/// Returns the entry of `s`, an exact match is preferred over a folded one.
+	#[must_use]
+	pub fn get(&self, s: &str) -> Option<&odict::Entry> {
+		self.odict.entries.get(s).or_else(|| {
+			let key = self.folding.fold(s);
+			let ordinal = self.index.ordinal(&key)?;
+			let term = self
+				.originals
+				.get(&ordinal)
+				.and_then(|originals| originals.first())
+				.unwrap_or(&key);
+			self.odict.entries.get(term.as_str())
+		})
+	}
+
+	/// Push original terms of the folded term `key` at `ordinal` to `result`.
+	fn push_originals(&self, result: &mut Vec<String>, ordinal: usize, key: String) {
+		match self.originals.get(&ordinal) {
+			Some(originals) => result.extend(originals.iter().cloned()),
+			None => result.push(key),
+		}
+	}
+}
+
+/// Iterator over original terms matching a prefix, see [`Dictionary::matches`].
+pub struct Matches<'a> {
+	dict: &'a Dictionary,
+	terms: Terms<'a>,
+	/// Originals of the last folded term which are not yielded yet, in reverse order.
+	pending: Vec<String>,
+}
+
+impl Matches<'_> {
+	/// Number of original terms of the folded term at `ordinal`.
+	fn originals_count(&self, ordinal: usize) -> usize {
+		self.dict.originals.get(&ordinal).map_or(1, Vec::len)
+	}
+}
+
+impl Iterator for Matches<'_> {
+	type Item = String;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if let Some(term) = self.pending.pop() {
+			return Some(term);
+		}
+
+		let ordinal = self.terms.ordinal();
+		let key = self.terms.next()?;
+		match self.dict.originals.get(&ordinal) {
+			Some(originals) => {
+				self.pending = originals.iter().rev().cloned().collect();
+				self.pending.pop()
+			}
+			None => Some(key),
+		}
+	}
+
+	/// Skip whole folded terms without building them, which makes `skip` cheap.
+	fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+		let pending = n.min(self.pending.len());
+		self.pending.truncate(self.pending.len() - pending);
+		n -= pending;
+		if n == 0 || !self.pending.is_empty() {
+			return self.next();
+		}
+
+		let mut skipped = 0;
+		let mut ordinal = self.terms.ordinal();
+		while skipped < self.terms.len() && n >= self.originals_count(ordinal) {
+			n -= self.originals_count(ordinal);
+			skipped += 1;
+			ordinal += 1;
+		}
+		if skipped > 0 {
+			self.terms.nth(skipped - 1);
+		}
+
+		// the rest are originals of the next folded term
+		for _ in 0..n {
+			self.next();
+		}
+		self.next()
+	}
 }
 
 impl From<odict::Dictionary> for Dictionary {
@@ -45,3 +356,57 @@ impl From<odict::Dictionary> for Dictionary {
 		Self::new(dict)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn dictionary(terms: &[&str]) -> Dictionary {
+		let mut odict = odict::Dictionary::default();
+		for term in terms {
+			odict.entries.insert(odict::Entry {
+				term: (*term).to_string(),
+				..Default::default()
+			});
+		}
+
+		Dictionary::new(odict)
+	}
+
+	#[test]
+	fn matches_originals() {
+		let dict = dictionary(&["Polish", "polish", "pole", "Pôle", "pool"]);
+
+		assert_eq!(
+			dict.matches("po").collect::<Vec<_>>(),
+			["Pôle", "pole", "Polish", "polish", "pool"]
+		);
+		assert!(dict.matches("x").next().is_none());
+	}
+
+	#[test]
+	fn matches_nth() {
+		let dict = dictionary(&["Polish", "polish", "pole", "Pôle", "pool", "port", "Port"]);
+		let all = dict.matches("").collect::<Vec<_>>();
+
+		for n in 0..=all.len() {
+			assert_eq!(dict.matches("").nth(n), all.get(n).cloned(), "{n}");
+			assert_eq!(
+				dict.matches("").skip(n).collect::<Vec<_>>(),
+				all[n..],
+				"{n}"
+			);
+		}
+
+		// skipping from inside the originals of a folded term
+		for start in 0..all.len() {
+			for n in 0..all.len() - start {
+				let mut matches = dict.matches("");
+				for _ in 0..start {
+					matches.next();
+				}
+				assert_eq!(matches.nth(n), all.get(start + n).cloned(), "{start} {n}");
+			}
+		}
+	}
+}
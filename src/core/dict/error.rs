@@ -0,0 +1,117 @@
+//! Errors of the dictionary core.
+
+use odict::semver::SemanticVersion;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Why an operation on dictionaries failed.
+///
+/// It's cheap to clone, so it can be sent in messages of the app.
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+	/// The dictionary at the path is not loaded yet.
+	#[error("dictionary {0:?} is not loaded")]
+	NotLoaded(PathBuf),
+	/// The `ODict` format version of a dictionary is not supported.
+	#[error("require ODict version ~{required}, but found {found}")]
+	IncompatibleVersion {
+		found: SemanticVersion,
+		required: SemanticVersion,
+	},
+	/// The path is not valid, e.g. it's not valid unicode or has no file name.
+	#[error("path not valid: {0:?}")]
+	InvalidPath(PathBuf),
+	/// The path exists, so it would be overwritten.
+	#[error("target path exists: {0:?}")]
+	AlreadyExists(PathBuf),
+	/// The archive has too many entries or is too large when extracted.
+	#[error("archive {0:?} is too large to extract")]
+	ArchiveTooLarge(PathBuf),
+	/// No dictionary is found in the directory or archive.
+	#[error("no dictionary found in {0:?}")]
+	NoDictionary(PathBuf),
+	/// The URL is not of a local file.
+	#[error("url is not a local file: {0}")]
+	InvalidUrl(String),
+	/// The search pattern is not valid, e.g. a bad regular expression.
+	#[error("{0}")]
+	InvalidPattern(String),
+	#[error(transparent)]
+	Io(Arc<io::Error>),
+	/// A data file could not be parsed, so it's moved to `backup` to not be
+	/// overwritten by the next save.
+	#[error("{reason}, the file is moved to {backup:?}")]
+	Unreadable { backup: PathBuf, reason: String },
+	/// The content is not in the expected format, e.g. a truncated `StarDict` index.
+	#[error("{0}")]
+	Format(String),
+	/// Reading or writing an `ODict` file failed in the `odict` library.
+	#[error("{0}")]
+	ODict(String),
+}
+
+impl Error {
+	pub(crate) fn format(message: impl Into<String>) -> Self {
+		Self::Format(message.into())
+	}
+
+	/// Wraps an error of the `odict` library, whose error type is kept out of the API.
+	pub(crate) fn odict(err: impl std::fmt::Display) -> Self {
+		Self::ODict(err.to_string())
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(err: io::Error) -> Self {
+		Self::Io(Arc::new(err))
+	}
+}
+
+impl From<zip::result::ZipError> for Error {
+	fn from(err: zip::result::ZipError) -> Self {
+		match err {
+			zip::result::ZipError::Io(err) => err.into(),
+			err => Self::Format(err.to_string()),
+		}
+	}
+}
+
+impl From<regex::Error> for Error {
+	fn from(err: regex::Error) -> Self {
+		Self::InvalidPattern(err.to_string())
+	}
+}
+
+impl From<std::num::TryFromIntError> for Error {
+	fn from(err: std::num::TryFromIntError) -> Self {
+		Self::Format(err.to_string())
+	}
+}
+
+impl From<std::array::TryFromSliceError> for Error {
+	fn from(err: std::array::TryFromSliceError) -> Self {
+		Self::Format(err.to_string())
+	}
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+	fn from(err: std::string::FromUtf8Error) -> Self {
+		Self::Format(err.to_string())
+	}
+}
+
+impl From<std::num::ParseIntError> for Error {
+	fn from(err: std::num::ParseIntError) -> Self {
+		Self::Format(err.to_string())
+	}
+}
+
+impl From<serde_json::Error> for Error {
+	fn from(err: serde_json::Error) -> Self {
+		Self::Format(err.to_string())
+	}
+}
@@ -0,0 +1,112 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Folds terms into keys so that lookups ignore case, Unicode normalization form
+/// and optionally accents, e.g. "Café", "cafe" and a decomposed "café" share the
+/// key "cafe".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Folding {
+	/// Whether to remove diacritics (accents, umlauts, etc.) of Latin, Greek and
+	/// Cyrillic letters from the key, see [`is_diacritic`].
+	pub strip_accents: bool,
+}
+
+impl Folding {
+	#[must_use]
+	pub fn new(strip_accents: bool) -> Self {
+		Self { strip_accents }
+	}
+
+	/// Returns the folded key of `s`.
+	#[must_use]
+	pub fn fold(&self, s: &str) -> String {
+		let lower: String = s
+			.nfkd()
+			.filter(|c| !(self.strip_accents && is_diacritic(*c)))
+			.flat_map(char::to_lowercase)
+			.collect();
+
+		// `to_lowercase` is not a full case folding, patch the common differences
+		lower.replace('ß', "ss").replace('ς', "σ").nfc().collect()
+	}
+}
+
+/// Whether `c` is a combining diacritic of Latin, Greek or Cyrillic letters.
+///
+/// Other combining marks are kept, since they make different letters, e.g. the
+/// voiced sound marks of kana in "が" and "ぱ".
+fn is_diacritic(c: char) -> bool {
+	matches!(c,
+		'\u{300}'..='\u{36f}'
+		| '\u{483}'..='\u{489}'
+		| '\u{1ab0}'..='\u{1aff}'
+		| '\u{1dc0}'..='\u{1dff}'
+		| '\u{20d0}'..='\u{20ff}'
+		| '\u{fe20}'..='\u{fe2f}')
+}
+
+impl Default for Folding {
+	fn default() -> Self {
+		Self::new(true)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fold_case() {
+		let folding = Folding::default();
+
+		assert_eq!(folding.fold("RUN"), "run");
+		assert_eq!(folding.fold("Straße"), "strasse");
+		assert_eq!(folding.fold("STRASSE"), "strasse");
+		assert_eq!(folding.fold("ΟΔΟΣ"), folding.fold("οδος"));
+		assert_eq!(folding.fold("οδός"), "οδοσ");
+	}
+
+	#[test]
+	fn fold_accents() {
+		let folding = Folding::default();
+
+		assert_eq!(folding.fold("Café"), "cafe");
+		assert_eq!(folding.fold("naïve"), "naive");
+		assert_eq!(folding.fold("Ångström"), "angstrom");
+		assert_eq!(folding.fold("日本語"), "日本語");
+		assert_eq!(folding.fold("Ёлка"), "елка");
+	}
+
+	#[test]
+	fn fold_kana() {
+		let folding = Folding::default();
+
+		for (voiced, unvoiced) in [("ガ", "カ"), ("ぱ", "は"), ("ば", "は"), ("ヴ", "ウ")] {
+			assert_eq!(folding.fold(voiced), voiced);
+			assert_ne!(folding.fold(voiced), folding.fold(unvoiced));
+		}
+		// decomposed and half-width forms are the same
+		assert_eq!(folding.fold("\u{304b}\u{3099}"), "が");
+		assert_eq!(folding.fold("ｶﾞ"), "ガ");
+	}
+
+	#[test]
+	fn fold_normalization_forms() {
+		let nfc = "caf\u{e9}";
+		let nfd = "cafe\u{301}";
+
+		for folding in [Folding::new(true), Folding::new(false)] {
+			assert_eq!(folding.fold(nfc), folding.fold(nfd));
+		}
+		assert_eq!(Folding::new(false).fold(nfd), nfc);
+	}
+
+	#[test]
+	fn accent_sensitive() {
+		let folding = Folding::new(false);
+
+		assert_eq!(folding.fold("Café"), "café");
+		assert_eq!(folding.fold("NAÏVE"), "naïve");
+		assert_ne!(folding.fold("résumé"), folding.fold("resume"));
+		assert_eq!(folding.fold("Straße"), "strasse");
+	}
+}
@@ -0,0 +1,437 @@
+//! Full-text search over definitions, examples and notes of entries.
+//!
+//! Every entry is a document, whose tokens are mapped to postings in an inverted
+//! index. Results are ranked by [BM25](https://en.wikipedia.org/wiki/Okapi_BM25).
+
+use super::cache::{put_bytes, put_u32s, take_str, take_u32, take_u32s};
+use super::{Error, Folding, Result, TermIndex};
+use crate::{elapsed_secs, now};
+use odict::DefinitionType;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::info;
+
+/// Term frequency saturation of BM25.
+const K1: f64 = 1.2;
+/// Document length normalization of BM25.
+const B: f64 = 0.75;
+
+/// Common English words which match almost every document, sorted.
+const STOP_WORDS: [&str; 40] = [
+	"a", "an", "and", "any", "are", "as", "at", "be", "been", "being", "by", "for", "from", "has",
+	"have", "in", "into", "is", "it", "its", "of", "on", "or", "so", "some", "such", "than",
+	"that", "the", "their", "this", "those", "to", "was", "were", "which", "who", "will", "with",
+	"you",
+];
+
+/// An inverted index from tokens to entries containing them.
+#[derive(Debug, Clone, Default)]
+pub struct FullTextIndex {
+	/// Terms of entries, indexed by document IDs.
+	docs: Vec<String>,
+	/// Number of tokens in each document.
+	doc_lens: Vec<u32>,
+	/// Index of tokens, whose ordinals index `offsets`.
+	tokens: TermIndex,
+	/// Postings of the token at ordinal `i` are at `offsets[i]..offsets[i + 1]`.
+	offsets: Vec<u32>,
+	/// Document IDs of postings, ascending for each token.
+	posting_docs: Vec<u32>,
+	/// Number of times the token occurs in the document of each posting.
+	posting_freqs: Vec<u32>,
+}
+
+impl FullTextIndex {
+	/// Index entries of `odict`, tokens are folded by `folding`.
+	#[must_use]
+	pub fn build(odict: &odict::Dictionary, folding: Folding) -> Self {
+		let t0 = now();
+		let mut entries: Vec<&odict::Entry> = Vec::new();
+		entries.extend(&odict.entries);
+		entries.sort_by(|a, b| a.term.cmp(&b.term));
+
+		let mut index = Self::default();
+		let mut postings: BTreeMap<String, Vec<(u32, u32)>> = BTreeMap::new();
+		for (doc, entry) in (0..).zip(entries) {
+			let mut freqs: HashMap<String, u32> = HashMap::new();
+			let mut len = 0;
+			for text in entry_texts(entry) {
+				for token in tokenize(folding, text) {
+					*freqs.entry(token).or_default() += 1;
+					len += 1;
+				}
+			}
+			for (token, freq) in freqs {
+				postings.entry(token).or_default().push((doc, freq));
+			}
+			index.docs.push(entry.term.clone());
+			index.doc_lens.push(len);
+		}
+
+		index.tokens = TermIndex::from_sorted(postings.keys());
+		index.offsets.push(0);
+		for (_, postings) in postings {
+			for (doc, freq) in postings {
+				index.posting_docs.push(doc);
+				index.posting_freqs.push(freq);
+			}
+			index
+				.offsets
+				.push(u32::try_from(index.posting_docs.len()).unwrap_or(u32::MAX));
+		}
+		info!(
+			"build full-text index of {} entries and {} tokens in {:.3}s",
+			index.docs.len(),
+			index.tokens.len(),
+			elapsed_secs(&t0)
+		);
+
+		index
+	}
+
+	/// Returns at most `limit` terms of entries matching any token of `query`, with
+	/// their scores, best first.
+	#[must_use]
+	// numbers of documents and tokens are far below 2^52
+	#[allow(clippy::cast_precision_loss)]
+	pub fn search(&self, folding: Folding, query: &str, limit: usize) -> Vec<(String, f64)> {
+		if self.docs.is_empty() {
+			return Vec::new();
+		}
+
+		let doc_count = self.docs.len() as f64;
+		let avg_len = self.doc_lens.iter().map(|len| f64::from(*len)).sum::<f64>() / doc_count;
+		let mut scores: HashMap<u32, f64> = HashMap::new();
+		let mut seen = HashSet::new();
+		for token in tokenize(folding, query) {
+			if !seen.insert(token.clone()) {
+				continue;
+			}
+			let Some(ordinal) = self.tokens.ordinal(&token) else {
+				continue;
+			};
+
+			let postings = self.offsets[ordinal] as usize..self.offsets[ordinal + 1] as usize;
+			let doc_freq = postings.len() as f64;
+			let idf = (1.0 + (doc_count - doc_freq + 0.5) / (doc_freq + 0.5)).ln();
+			for i in postings {
+				let doc = self.posting_docs[i];
+				let freq = f64::from(self.posting_freqs[i]);
+				let len = f64::from(self.doc_lens[doc as usize]);
+				let tf = freq * (K1 + 1.0) / (freq + K1 * (1.0 - B + B * len / avg_len));
+				*scores.entry(doc).or_default() += idf * tf;
+			}
+		}
+
+		let mut scores: Vec<(u32, f64)> = scores.into_iter().collect();
+		// ties are broken by document IDs, i.e. by terms, so results are stable
+		scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+		scores
+			.into_iter()
+			.take(limit)
+			.map(|(doc, score)| (self.docs[doc as usize].clone(), score))
+			.collect()
+	}
+
+	/// Serialize the index, see [`IndexCache`](super::IndexCache).
+	pub(crate) fn write_to(&self, buf: &mut Vec<u8>) -> Result<()> {
+		buf.extend_from_slice(&u32::try_from(self.docs.len())?.to_le_bytes());
+		for term in &self.docs {
+			put_bytes(buf, term.as_bytes());
+		}
+		put_u32s(buf, &self.doc_lens);
+		self.tokens.write_to(buf);
+		put_u32s(buf, &self.offsets);
+		put_u32s(buf, &self.posting_docs);
+		put_u32s(buf, &self.posting_freqs);
+
+		Ok(())
+	}
+
+	/// Deserialize an index written by [`Self::write_to`], advancing `bytes`.
+	pub(crate) fn read_from(bytes: &mut &[u8]) -> Result<Self> {
+		let n = take_u32(bytes)?;
+		let docs = (0..n)
+			.map(|_| take_str(bytes))
+			.collect::<Result<Vec<_>>>()?;
+		let index = Self {
+			docs,
+			doc_lens: take_u32s(bytes)?,
+			tokens: TermIndex::read_from(bytes)?,
+			offsets: take_u32s(bytes)?,
+			posting_docs: take_u32s(bytes)?,
+			posting_freqs: take_u32s(bytes)?,
+		};
+
+		let postings = index.posting_docs.len();
+		let is_valid = index.doc_lens.len() == index.docs.len()
+			&& index.offsets.len() == index.tokens.len() + 1
+			&& index.offsets.first() == Some(&0)
+			&& index.offsets.windows(2).all(|w| w[0] <= w[1])
+			&& index
+				.offsets
+				.last()
+				.is_some_and(|last| *last as usize == postings)
+			&& index.posting_freqs.len() == postings
+			&& index
+				.posting_docs
+				.iter()
+				.all(|doc| (*doc as usize) < index.docs.len());
+		if !is_valid {
+			return Err(Error::format("invalid full-text index"));
+		}
+
+		Ok(index)
+	}
+}
+
+/// Merge rankings of several dictionaries into at most `limit` unique terms.
+///
+/// Scores of different indexes are not comparable, because IDF and the average
+/// document length are computed per index, so results are interleaved by rank instead:
+/// the best of each ranking first, then the second best, and so on.
+#[must_use]
+pub fn interleave_rankings(rankings: Vec<Vec<(String, f64)>>, limit: usize) -> Vec<String> {
+	let mut rankings: Vec<_> = rankings.into_iter().map(Vec::into_iter).collect();
+	let mut seen = HashSet::new();
+	let mut result = Vec::new();
+	while result.len() < limit {
+		let mut exhausted = true;
+		for ranking in &mut rankings {
+			let Some((term, _)) = ranking.next() else {
+				continue;
+			};
+			exhausted = false;
+			if result.len() < limit && seen.insert(term.clone()) {
+				result.push(term);
+			}
+		}
+		if exhausted {
+			break;
+		}
+	}
+
+	result
+}
+
+/// Returns texts of `entry` to index: descriptions, definitions, examples and notes.
+fn entry_texts(entry: &odict::Entry) -> Vec<&str> {
+	fn push_definition<'a>(texts: &mut Vec<&'a str>, def: &'a odict::Definition) {
+		texts.push(&def.value);
+		texts.extend(def.examples.iter().map(|example| example.value.as_str()));
+		texts.extend(def.notes.iter().map(|note| note.value.as_str()));
+	}
+
+	let mut texts = Vec::new();
+	for ety in &entry.etymologies {
+		texts.extend(ety.description.as_deref());
+		for sense in &ety.senses {
+			for def in &sense.definitions {
+				match def {
+					DefinitionType::Definition(def) => push_definition(&mut texts, def),
+					DefinitionType::Group(group) => {
+						texts.push(&group.description);
+						for def in &group.definitions {
+							push_definition(&mut texts, def);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	texts
+}
+
+/// Split `text` after folding into tokens, which are runs of alphanumeric chars, or
+/// single Han and Kana chars since words of those scripts are not separated by
+/// spaces. Hangul is spaced, so it's split like alphabets. Stop words are left out.
+fn tokenize(folding: Folding, text: &str) -> Vec<String> {
+	let mut tokens = Vec::new();
+	let mut token = String::new();
+	for c in folding.fold(text).chars() {
+		if c.is_alphanumeric() && !is_han_or_kana(c) {
+			token.push(c);
+			continue;
+		}
+		if !token.is_empty() {
+			tokens.push(std::mem::take(&mut token));
+		}
+		if is_han_or_kana(c) {
+			tokens.push(c.to_string());
+		}
+	}
+	if !token.is_empty() {
+		tokens.push(token);
+	}
+	tokens.retain(|token| STOP_WORDS.binary_search(&token.as_str()).is_err());
+
+	tokens
+}
+
+fn is_han_or_kana(c: char) -> bool {
+	matches!(c,
+		'\u{3040}'..='\u{30ff}' // Hiragana and Katakana
+		| '\u{3400}'..='\u{4dbf}' // CJK Unified Ideographs Extension A
+		| '\u{4e00}'..='\u{9fff}' // CJK Unified Ideographs
+		| '\u{f900}'..='\u{faff}' // CJK Compatibility Ideographs
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entry(term: &str, definition: &str, description: &str) -> odict::Entry {
+		let sense = odict::Sense {
+			definitions: vec![DefinitionType::Definition(odict::Definition {
+				value: definition.to_string(),
+				..Default::default()
+			})],
+			..Default::default()
+		};
+		odict::Entry {
+			term: term.to_string(),
+			etymologies: vec![odict::Etymology {
+				description: Some(description.to_string()),
+				senses: std::iter::once(sense).collect(),
+				..Default::default()
+			}],
+			..Default::default()
+		}
+	}
+
+	fn index(entries: &[(&str, &str, &str)]) -> FullTextIndex {
+		let mut odict = odict::Dictionary::default();
+		for (term, definition, description) in entries {
+			odict.entries.insert(entry(term, definition, description));
+		}
+
+		FullTextIndex::build(&odict, Folding::default())
+	}
+
+	fn terms(results: Vec<(String, f64)>) -> Vec<String> {
+		results.into_iter().map(|(term, _)| term).collect()
+	}
+
+	#[test]
+	fn tokenize_text() {
+		let folding = Folding::default();
+
+		assert_eq!(
+			tokenize(folding, "The Café, a well-known place!"),
+			["cafe", "well", "known", "place"]
+		);
+		assert_eq!(tokenize(folding, "A4 paper"), ["a4", "paper"]);
+		assert_eq!(tokenize(folding, "日本語 text"), ["日", "本", "語", "text"]);
+		assert_eq!(
+			tokenize(folding, "ひらがなカナ"),
+			["ひ", "ら", "が", "な", "カ", "ナ"]
+		);
+		assert_eq!(
+			tokenize(folding, "한국어 사전, 단어"),
+			["한국어", "사전", "단어"]
+		);
+		assert_eq!(tokenize(Folding::new(false), "Café"), ["café"]);
+		assert!(tokenize(folding, "Of the, AND to...").is_empty());
+	}
+
+	#[test]
+	fn stop_words_sorted() {
+		assert!(STOP_WORDS.is_sorted());
+		for word in STOP_WORDS {
+			assert_eq!(Folding::default().fold(word), word);
+		}
+	}
+
+	#[test]
+	fn search_ranked() {
+		let index = index(&[
+			("dash", "fast fast", ""),
+			("jog", "run slowly, not fast at all but steady", ""),
+			("sprint", "run fast", ""),
+			("walk", "move on foot", "from a walk in the park"),
+		]);
+
+		// more occurrences in shorter documents first
+		assert_eq!(
+			terms(index.search(Folding::default(), "fast", 10)),
+			["dash", "sprint", "jog"]
+		);
+		// documents with more and rarer tokens of the query first
+		assert_eq!(
+			terms(index.search(Folding::default(), "Steady run", 10)),
+			["jog", "sprint"]
+		);
+		assert_eq!(
+			terms(index.search(Folding::default(), "park", 10)),
+			["walk"]
+		);
+		assert_eq!(
+			terms(index.search(Folding::default(), "fast", 2)),
+			["dash", "sprint"]
+		);
+		assert!(index.search(Folding::default(), "swim", 10).is_empty());
+		assert!(index.search(Folding::default(), "the", 10).is_empty());
+		assert!(
+			FullTextIndex::default()
+				.search(Folding::default(), "fast", 10)
+				.is_empty()
+		);
+	}
+
+	#[test]
+	fn search_ties_by_term() {
+		let index = index(&[("b", "fast", ""), ("c", "fast", ""), ("a", "fast", "")]);
+
+		let results = index.search(Folding::default(), "fast", 10);
+		assert!(
+			results
+				.windows(2)
+				.all(|pair| pair[0].1.total_cmp(&pair[1].1).is_eq())
+		);
+		assert_eq!(terms(results), ["a", "b", "c"]);
+	}
+
+	#[test]
+	fn read_written() {
+		let index = index(&[("dash", "fast fast", ""), ("walk", "move on foot", "")]);
+		let mut bytes = Vec::new();
+		index.write_to(&mut bytes).unwrap();
+
+		let mut rest = bytes.as_slice();
+		let read = FullTextIndex::read_from(&mut rest).unwrap();
+		assert!(rest.is_empty());
+		assert_eq!(
+			read.search(Folding::default(), "fast foot", 10),
+			index.search(Folding::default(), "fast foot", 10)
+		);
+
+		for len in 0..bytes.len() {
+			let result = FullTextIndex::read_from(&mut &bytes[..len]);
+			assert!(matches!(result, Err(Error::Format(_))), "{len}");
+		}
+	}
+
+	#[test]
+	fn interleave() {
+		let ranking = |terms: &[&str]| -> Vec<(String, f64)> {
+			terms
+				.iter()
+				.map(|term| ((*term).to_string(), 1.0))
+				.collect()
+		};
+		let rankings = vec![
+			ranking(&["a1", "a2", "a3"]),
+			ranking(&["b1", "a1"]),
+			Vec::new(),
+			ranking(&["c1"]),
+		];
+
+		assert_eq!(
+			interleave_rankings(rankings.clone(), 10),
+			["a1", "b1", "c1", "a2", "a3"]
+		);
+		assert_eq!(interleave_rankings(rankings, 2), ["a1", "b1"]);
+		assert!(interleave_rankings(Vec::new(), 10).is_empty());
+	}
+}
@@ -0,0 +1,750 @@
+//! Import dictionaries from files, archives and directories.
+
+use super::cache::hash_file;
+use super::{
+	DictFileInfo, DictStore, Error, Result, odict_file_name, read_dictionary_from_path,
+	read_odict_from_path, write_new_odict_atomically, write_odict_atomically,
+};
+use crate::utils::TempDir;
+use crate::{elapsed_secs, now, unique_suffix};
+use flate2::read::GzDecoder;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tracing::{info, info_span, warn};
+use url::Url;
+
+/// Extensions of dictionary files to import, `StarDict` dictionaries are found by
+/// their `.ifo` files.
+const DICTIONARY_EXTENSIONS: [&str; 2] = ["odict", "ifo"];
+
+/// Extension of imported dictionaries waiting for an [`ImportConflict`] to be
+/// resolved, which are not loaded since it's not `odict`. They are removed by
+/// [`remove_pending_files`] if they are left.
+const PENDING: &str = "pending";
+
+/// What happened to an imported dictionary.
+#[derive(Debug, Clone)]
+pub enum ImportStatus {
+	/// Imported to the path.
+	Imported(PathBuf),
+	/// Not imported, since the same dictionary is already at the path.
+	Unchanged(PathBuf),
+	/// Not imported, since the existing dictionary is kept.
+	Skipped,
+	/// Another dictionary of the same name is imported before.
+	Conflict(ImportConflict),
+	Failed(Error),
+}
+
+/// Outcome of importing one dictionary file.
+#[derive(Debug, Clone)]
+pub struct ImportReport {
+	/// The dictionary file, which is joined to the archive path if it's in one.
+	pub source: PathBuf,
+	pub status: ImportStatus,
+}
+
+impl ImportReport {
+	#[must_use]
+	pub fn new(source: PathBuf, result: Result<ImportStatus>) -> Self {
+		Self {
+			source,
+			status: result.unwrap_or_else(ImportStatus::Failed),
+		}
+	}
+}
+
+/// How to resolve an [`ImportConflict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+	/// Replace the existing dictionary with the new one.
+	Replace,
+	/// Keep both, the new one is renamed with a number suffix.
+	KeepBoth,
+	/// Keep the existing dictionary only.
+	Skip,
+}
+
+/// A dictionary to import has the same name as an imported one, but a different
+/// content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportConflict {
+	/// Name of both dictionaries.
+	pub name: String,
+	/// The new dictionary, which is written next to `existing` until the conflict is
+	/// resolved.
+	pub incoming: DictFileInfo,
+	pub existing: DictFileInfo,
+	/// Content hashes of `incoming` and `existing`.
+	pub hashes: (u64, u64),
+}
+
+impl ImportConflict {
+	fn new(name: String, incoming: &Path, existing: &Path) -> Result<Self> {
+		Ok(Self {
+			name,
+			incoming: DictFileInfo::read(incoming)?,
+			existing: DictFileInfo::read(existing)?,
+			hashes: (hash_file(incoming)?, hash_file(existing)?),
+		})
+	}
+
+	/// Resolve the conflict, returns the path of the new dictionary unless it's
+	/// skipped. The existing dictionary is replaced by a rename, which is atomic.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if the new dictionary could not be moved, renamed or removed
+	pub fn resolve(&self, resolution: ConflictResolution) -> Result<Option<PathBuf>> {
+		let pending = &self.incoming.path;
+		match resolution {
+			ConflictResolution::Replace => {
+				fs::rename(pending, &self.existing.path)?;
+				info!("replaced {:?} with the new dictionary", self.existing.path);
+				Ok(Some(self.existing.path.clone()))
+			}
+			ConflictResolution::KeepBoth => {
+				let dir = self
+					.existing
+					.path
+					.parent()
+					.ok_or_else(|| Error::InvalidPath(self.existing.path.clone()))?;
+				let mut odict = read_odict_from_path(pending)?;
+				for n in 2..=u16::MAX {
+					let name = format!("{} ({n})", self.name);
+					let path = dir.join(odict_file_name(&name));
+					odict.name = Some(name);
+					// another import may take the name meanwhile
+					match write_new_odict_atomically(&odict, &path) {
+						Ok(()) => {
+							fs::remove_file(pending)?;
+							info!("imported the new dictionary to {path:?}");
+							return Ok(Some(path));
+						}
+						Err(Error::AlreadyExists(_)) => {}
+						Err(err) => return Err(err),
+					}
+				}
+
+				Err(Error::AlreadyExists(dir.join(odict_file_name(&self.name))))
+			}
+			ConflictResolution::Skip => {
+				fs::remove_file(pending)?;
+				Ok(None)
+			}
+		}
+	}
+}
+
+/// Supported archive formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+	Zip,
+	Tar,
+	TarGz,
+	TarXz,
+}
+
+impl ArchiveKind {
+	/// File name suffixes of archives, in lowercase.
+	pub const SUFFIXES: [(&str, Self); 6] = [
+		(".zip", Self::Zip),
+		(".tar", Self::Tar),
+		(".tar.gz", Self::TarGz),
+		(".tgz", Self::TarGz),
+		(".tar.xz", Self::TarXz),
+		(".txz", Self::TarXz),
+	];
+
+	/// Returns the archive format of `path` by its file name.
+	#[must_use]
+	pub fn of(path: &Path) -> Option<Self> {
+		let name = path.file_name()?.to_string_lossy().to_lowercase();
+		Self::SUFFIXES
+			.iter()
+			.find(|(suffix, _)| name.ends_with(suffix))
+			.map(|(_, kind)| *kind)
+	}
+
+	/// Extract the archive at `path` into `dir`, entries which would be outside `dir`
+	/// are skipped.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if the archive could not be read or extracted, or it's larger
+	/// than [`ExtractLimits`]
+	pub fn extract(self, path: &Path, dir: &Path) -> Result<()> {
+		self.extract_with_limits(path, dir, ExtractLimits::new(path))
+	}
+
+	fn extract_with_limits(self, path: &Path, dir: &Path, limits: ExtractLimits) -> Result<()> {
+		let file = File::open(path)?;
+		match self {
+			Self::Zip => unpack_zip(file, dir, limits),
+			Self::Tar => unpack_tar(file, dir, limits),
+			Self::TarGz => unpack_tar(GzDecoder::new(file), dir, limits),
+			Self::TarXz => unpack_tar(xz2::read::XzDecoder::new(file), dir, limits),
+		}
+	}
+}
+
+/// Most entries and bytes extracted from an archive, so a crafted one can't fill the
+/// disk.
+#[derive(Debug, Clone)]
+struct ExtractLimits {
+	/// The archive, which is reported if it's too large.
+	path: PathBuf,
+	entries: usize,
+	bytes: u64,
+}
+
+impl ExtractLimits {
+	const MAX_ENTRIES: usize = 10_000;
+	const MAX_BYTES: u64 = 4 << 30;
+
+	fn new(path: &Path) -> Self {
+		Self {
+			path: path.to_path_buf(),
+			entries: Self::MAX_ENTRIES,
+			bytes: Self::MAX_BYTES,
+		}
+	}
+
+	/// Count an entry to extract.
+	fn take_entry(&mut self) -> Result<()> {
+		self.entries = self.entries.checked_sub(1).ok_or_else(|| self.exceeded())?;
+
+		Ok(())
+	}
+
+	/// Count `bytes` to extract.
+	fn take_bytes(&mut self, bytes: u64) -> Result<()> {
+		self.bytes = self
+			.bytes
+			.checked_sub(bytes)
+			.ok_or_else(|| self.exceeded())?;
+
+		Ok(())
+	}
+
+	/// Copy at most the bytes left from `reader` to `writer`, sizes in zip headers may
+	/// be wrong.
+	fn copy(&mut self, reader: impl Read, writer: &mut impl io::Write) -> Result<()> {
+		// one more byte to tell if it's exceeded
+		let copied = io::copy(&mut reader.take(self.bytes.saturating_add(1)), writer)?;
+		self.take_bytes(copied)
+	}
+
+	fn exceeded(&self) -> Error {
+		Error::ArchiveTooLarge(self.path.clone())
+	}
+}
+
+fn unpack_zip(file: File, dir: &Path, mut limits: ExtractLimits) -> Result<()> {
+	let mut archive = zip::ZipArchive::new(file)?;
+	for i in 0..archive.len() {
+		limits.take_entry()?;
+		let mut entry = archive.by_index(i)?;
+		let Some(name) = entry.enclosed_name() else {
+			warn!("skip {:?} outside of the archive", entry.name());
+			continue;
+		};
+		let path = dir.join(name);
+		if entry.is_dir() {
+			fs::create_dir_all(&path)?;
+			continue;
+		}
+
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		limits.copy(&mut entry, &mut File::create(&path)?)?;
+	}
+
+	Ok(())
+}
+
+fn unpack_tar(reader: impl Read, dir: &Path, mut limits: ExtractLimits) -> Result<()> {
+	let mut archive = tar::Archive::new(reader);
+	for entry in archive.entries()? {
+		limits.take_entry()?;
+		let mut entry = entry?;
+		// entries never yield more than the size in their headers
+		limits.take_bytes(entry.size())?;
+		entry.unpack_in(dir)?;
+	}
+
+	Ok(())
+}
+
+/// Returns whether `path` is a dictionary file to import.
+fn is_dictionary_file(path: &Path) -> bool {
+	path.extension().is_some_and(|ext| {
+		DICTIONARY_EXTENSIONS
+			.iter()
+			.any(|e| ext.eq_ignore_ascii_case(e))
+	})
+}
+
+/// Find dictionary files in `dir` recursively, and archives too if `archives` is
+/// `true`. Symbolic links are not followed, so there are no cycles.
+fn find_dictionaries(dir: &Path, archives: bool, found: &mut Vec<PathBuf>) -> io::Result<()> {
+	for entry in fs::read_dir(dir)? {
+		let entry = entry?;
+		let path = entry.path();
+		let file_type = entry.file_type()?;
+		if file_type.is_dir() {
+			find_dictionaries(&path, archives, found)?;
+		} else if file_type.is_file()
+			&& (is_dictionary_file(&path) || archives && ArchiveKind::of(&path).is_some())
+		{
+			found.push(path);
+		}
+	}
+
+	Ok(())
+}
+
+/// Import the dictionary file at `path` to the data directory of `store`.
+///
+/// If a dictionary of the same name is imported before, the new one is written to
+/// a pending file and a [`ImportConflict`] is returned, unless they are the same.
+///
+/// # Errors
+///
+/// Will return `Err` if the dictionary could not be read or written
+pub fn import_dictionary_file(path: &Path, store: &DictStore) -> Result<ImportStatus> {
+	info!("reading dictionary from {}...", path.display());
+	let mut odict = read_dictionary_from_path(path)?;
+
+	if !store.data_dir.exists() {
+		fs::create_dir_all(&store.data_dir)?;
+	}
+
+	let name = if let Some(name) = &odict.name {
+		name.clone()
+	} else {
+		let name = path
+			.file_stem()
+			.ok_or_else(|| Error::InvalidPath(path.to_path_buf()))?
+			.to_string_lossy()
+			.to_string();
+		odict.name = Some(name.clone());
+		name
+	};
+	let target_path = store.data_dir.join(odict_file_name(&name));
+
+	info!("writing ODict to {target_path:?}...");
+	match write_new_odict_atomically(&odict, &target_path) {
+		Ok(()) => return Ok(ImportStatus::Imported(target_path)),
+		Err(Error::AlreadyExists(_)) => {}
+		Err(err) => return Err(err),
+	}
+
+	// conflicts of the same name may be pending at the same time
+	let pending_path = target_path.with_extension(format!("odict.{}.{PENDING}", unique_suffix()));
+	info!("{target_path:?} exists, writing ODict to {pending_path:?}...");
+	write_odict_atomically(&odict, &pending_path)?;
+	let conflict = ImportConflict::new(name, &pending_path, &target_path);
+	match conflict {
+		Ok(conflict) if conflict.hashes.0 == conflict.hashes.1 => {
+			info!("{target_path:?} is the same, skipping it");
+			fs::remove_file(&pending_path)?;
+			Ok(ImportStatus::Unchanged(target_path))
+		}
+		Ok(conflict) => Ok(ImportStatus::Conflict(conflict)),
+		Err(err) => {
+			fs::remove_file(&pending_path)?;
+			Err(err)
+		}
+	}
+}
+
+/// Remove pending files in the data directory of `store`, which are left if the app
+/// quits before their [`ImportConflict`]s are resolved.
+///
+/// Must not be called while any conflict is waiting to be resolved.
+///
+/// # Errors
+///
+/// Will return `Err` if the data directory could not be read or a file could not be
+/// removed
+pub fn remove_pending_files(store: &DictStore) -> Result<()> {
+	let entries = match fs::read_dir(&store.data_dir) {
+		Ok(entries) => entries,
+		Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+		Err(err) => return Err(err.into()),
+	};
+
+	for entry in entries {
+		let path = entry?.path();
+		if path.extension().is_some_and(|ext| ext == PENDING) && path.is_file() {
+			info!("removing pending {}", path.display());
+			fs::remove_file(&path)?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Import every dictionary file in the archive at `path`, archives in it are not
+/// extracted.
+fn import_archive(
+	path: &Path,
+	kind: ArchiveKind,
+	store: &DictStore,
+	reports: &mut Vec<ImportReport>,
+) -> Result<()> {
+	info!("extracting {}...", path.display());
+	let dir = TempDir::create("import")?;
+	kind.extract(path, dir.path())?;
+
+	let mut files = Vec::new();
+	find_dictionaries(dir.path(), false, &mut files)?;
+	if files.is_empty() {
+		return Err(Error::NoDictionary(path.to_path_buf()));
+	}
+	files.sort();
+	for file in files {
+		let source = path.join(file.strip_prefix(dir.path()).unwrap_or(&file));
+		reports.push(ImportReport::new(
+			source,
+			import_dictionary_file(&file, store),
+		));
+	}
+
+	Ok(())
+}
+
+/// Import the dictionary file or archive at `path`, a failed archive is reported
+/// as a whole.
+fn import_file_or_archive(path: &Path, store: &DictStore, reports: &mut Vec<ImportReport>) {
+	let status = match ArchiveKind::of(path) {
+		Some(kind) => match import_archive(path, kind, store, reports) {
+			Ok(()) => return,
+			Err(err) => Err(err),
+		},
+		None => import_dictionary_file(path, store),
+	};
+	reports.push(ImportReport::new(path.to_path_buf(), status));
+}
+
+/// Import dictionaries at `path` to `store`, which is a dictionary file, an archive of
+/// them, or a directory searched recursively for both.
+///
+/// Every dictionary is imported on its own, so one failure doesn't stop the rest.
+///
+/// # Errors
+///
+/// Will return `Err` if the directory could not be read or no dictionary is found
+pub fn import_dictionaries(path: &Path, store: &DictStore) -> Result<Vec<ImportReport>> {
+	let _span = info_span!("import").entered();
+	let t0 = now();
+
+	let mut reports = Vec::new();
+	if path.is_dir() {
+		let mut files = Vec::new();
+		find_dictionaries(path, true, &mut files)?;
+		if files.is_empty() {
+			return Err(Error::NoDictionary(path.to_path_buf()));
+		}
+		files.sort();
+		for file in files {
+			import_file_or_archive(&file, store, &mut reports);
+		}
+	} else {
+		import_file_or_archive(path, store, &mut reports);
+	}
+
+	let imported = reports
+		.iter()
+		.filter(|report| matches!(report.status, ImportStatus::Imported(_)))
+		.count();
+	info!(
+		"imported {imported} of {} dictionaries in {:.3}s",
+		reports.len(),
+		elapsed_secs(&t0)
+	);
+
+	Ok(reports)
+}
+
+/// Import dictionaries at the file `url`, see [`import_dictionaries`].
+///
+/// # Errors
+///
+/// Will return `Err` if `url` is not a file, or nothing could be imported from it
+pub fn import_odict(url: &Url, store: &DictStore) -> Result<Vec<ImportReport>> {
+	if url.scheme() != "file" {
+		return Err(Error::InvalidUrl(url.to_string()));
+	}
+	let path = url
+		.to_file_path()
+		.map_err(|()| Error::InvalidUrl(url.to_string()))?;
+
+	import_dictionaries(&path, store)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::write_odict_to_path;
+
+	/// Returns the store of a new data directory in `dir`.
+	fn store(dir: &TempDir) -> DictStore {
+		DictStore::new(dir.path().join("data"), dir.path().join("cache"))
+	}
+
+	/// Write a dictionary named `name` of `terms` to `file` in `dir` to import.
+	fn source(dir: &TempDir, file: &str, name: Option<&str>, terms: &[&str]) -> PathBuf {
+		let mut odict = odict::Dictionary {
+			name: name.map(str::to_string),
+			..Default::default()
+		};
+		for term in terms {
+			odict.entries.insert(odict::Entry {
+				term: (*term).to_string(),
+				..Default::default()
+			});
+		}
+		let path = dir.path().join(file);
+		write_odict_to_path(&odict, &path).unwrap();
+
+		path
+	}
+
+	fn terms(path: &Path) -> Vec<String> {
+		read_odict_from_path(path)
+			.unwrap()
+			.entries
+			.iter()
+			.map(|entry| entry.term.clone())
+			.collect()
+	}
+
+	fn files(dir: &Path) -> Vec<String> {
+		let mut files: Vec<String> = fs::read_dir(dir)
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+			.collect();
+		files.sort();
+
+		files
+	}
+
+	/// Import a dictionary conflicting with the imported "Test" dictionary.
+	fn conflict(dir: &TempDir, store: &DictStore, file: &str) -> ImportConflict {
+		let path = source(dir, file, Some("Test"), &["new"]);
+		match import_dictionary_file(&path, store).unwrap() {
+			ImportStatus::Conflict(conflict) => conflict,
+			status => panic!("{status:?}"),
+		}
+	}
+
+	/// Write a zip archive of `files` to `name` in `dir`.
+	fn zip(dir: &TempDir, name: &str, files: &[(&str, &str)]) -> PathBuf {
+		use std::io::Write;
+
+		let path = dir.path().join(name);
+		let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+		for (name, content) in files {
+			zip.start_file(*name, zip::write::SimpleFileOptions::default())
+				.unwrap();
+			zip.write_all(content.as_bytes()).unwrap();
+		}
+		zip.finish().unwrap();
+
+		path
+	}
+
+	/// Write a tar archive of `files` to `name` in `dir`.
+	fn tar(dir: &TempDir, name: &str, files: &[(&str, &str)]) -> PathBuf {
+		let path = dir.path().join(name);
+		let mut tar = tar::Builder::new(File::create(&path).unwrap());
+		for (name, content) in files {
+			let mut header = tar::Header::new_gnu();
+			header.set_size(content.len() as u64);
+			header.set_mode(0o644);
+			tar.append_data(&mut header, name, content.as_bytes())
+				.unwrap();
+		}
+		tar.finish().unwrap();
+
+		path
+	}
+
+	fn limits(path: &Path, entries: usize, bytes: u64) -> ExtractLimits {
+		ExtractLimits {
+			path: path.to_path_buf(),
+			entries,
+			bytes,
+		}
+	}
+
+	#[test]
+	fn extract_archives() {
+		let dir = TempDir::new("extract");
+		let files = [("a.odict", "12345"), ("sub/b.odict", "67890")];
+		let archives = [
+			(zip(&dir, "test.zip", &files), ArchiveKind::Zip),
+			(tar(&dir, "test.tar", &files), ArchiveKind::Tar),
+		];
+
+		for (path, kind) in archives {
+			let out = TempDir::new("extract-out");
+			kind.extract_with_limits(&path, out.path(), limits(&path, 2, 10))
+				.unwrap();
+			assert_eq!(
+				fs::read_to_string(out.path().join("a.odict")).unwrap(),
+				"12345"
+			);
+			assert_eq!(
+				fs::read_to_string(out.path().join("sub/b.odict")).unwrap(),
+				"67890"
+			);
+
+			for limits in [limits(&path, 1, 10), limits(&path, 2, 9)] {
+				let out = TempDir::new("extract-out");
+				assert!(matches!(
+					kind.extract_with_limits(&path, out.path(), limits),
+					Err(Error::ArchiveTooLarge(archive)) if archive == path
+				));
+			}
+		}
+	}
+
+	#[test]
+	fn extract_zip_outside() {
+		let dir = TempDir::new("extract-outside");
+		let path = zip(
+			&dir,
+			"test.zip",
+			&[("../evil.odict", "x"), ("ok.odict", "y")],
+		);
+
+		let out = TempDir::new("extract-outside-out");
+		ArchiveKind::Zip.extract(&path, out.path()).unwrap();
+		assert_eq!(files(out.path()), ["ok.odict"]);
+		assert!(!out.path().parent().unwrap().join("evil.odict").exists());
+	}
+
+	#[test]
+	fn import_new_and_unchanged() {
+		let dir = TempDir::new("import-new");
+		let store = store(&dir);
+		let path = source(&dir, "Test.odict", None, &["old"]);
+
+		let ImportStatus::Imported(imported) = import_dictionary_file(&path, &store).unwrap()
+		else {
+			panic!("not imported");
+		};
+		assert_eq!(imported, store.data_dir.join("Test.odict"));
+		assert_eq!(
+			read_odict_from_path(&imported).unwrap().name.as_deref(),
+			Some("Test")
+		);
+
+		let status = import_dictionary_file(&path, &store).unwrap();
+		assert!(matches!(status, ImportStatus::Unchanged(path) if path == imported));
+		assert_eq!(files(&store.data_dir), ["Test.odict"]);
+	}
+
+	#[test]
+	fn import_concurrently() {
+		let dir = TempDir::new("import-concurrent");
+		let store = store(&dir);
+		let path = source(&dir, "Test.odict", None, &["run"]);
+
+		let statuses: Vec<_> = std::thread::scope(|scope| {
+			let handles: Vec<_> = (0..8)
+				.map(|_| scope.spawn(|| import_dictionary_file(&path, &store).unwrap()))
+				.collect();
+			handles.into_iter().map(|h| h.join().unwrap()).collect()
+		});
+
+		let imported = statuses
+			.iter()
+			.filter(|status| matches!(status, ImportStatus::Imported(_)))
+			.count();
+		assert_eq!(imported, 1);
+		assert!(statuses.iter().all(|status| matches!(
+			status,
+			ImportStatus::Imported(_) | ImportStatus::Unchanged(_)
+		)));
+		assert_eq!(files(&store.data_dir), ["Test.odict"]);
+	}
+
+	#[test]
+	fn conflicts_pending() {
+		let dir = TempDir::new("import-pending");
+		let store = store(&dir);
+		let path = source(&dir, "old.odict", Some("Test"), &["old"]);
+		import_dictionary_file(&path, &store).unwrap();
+
+		let first = conflict(&dir, &store, "first.odict");
+		let second = conflict(&dir, &store, "second.odict");
+		assert_eq!(first.name, "Test");
+		assert_eq!(first.existing.path, store.data_dir.join("Test.odict"));
+		assert_ne!(first.hashes.0, first.hashes.1);
+		assert_ne!(first.incoming.path, second.incoming.path);
+		for conflict in [&first, &second] {
+			assert_eq!(conflict.incoming.path.extension().unwrap(), PENDING);
+			assert!(conflict.incoming.path.exists());
+		}
+
+		// left by a previous run
+		remove_pending_files(&store).unwrap();
+		assert_eq!(files(&store.data_dir), ["Test.odict"]);
+		assert_eq!(terms(&first.existing.path), ["old"]);
+	}
+
+	#[test]
+	fn remove_pending_without_data_dir() {
+		let dir = TempDir::new("import-no-data");
+		remove_pending_files(&store(&dir)).unwrap();
+	}
+
+	#[test]
+	fn resolve_conflicts() {
+		let dir = TempDir::new("import-resolve");
+		let store = store(&dir);
+		let path = source(&dir, "old.odict", Some("Test"), &["old"]);
+		import_dictionary_file(&path, &store).unwrap();
+		let existing = store.data_dir.join("Test.odict");
+
+		let skipped = conflict(&dir, &store, "skipped.odict");
+		assert_eq!(skipped.resolve(ConflictResolution::Skip).unwrap(), None);
+		assert_eq!(files(&store.data_dir), ["Test.odict"]);
+		assert_eq!(terms(&existing), ["old"]);
+
+		let kept = conflict(&dir, &store, "kept.odict");
+		let again = conflict(&dir, &store, "again.odict");
+		let path = kept.resolve(ConflictResolution::KeepBoth).unwrap();
+		assert_eq!(path, Some(store.data_dir.join("Test (2).odict")));
+		let path = again
+			.resolve(ConflictResolution::KeepBoth)
+			.unwrap()
+			.unwrap();
+		assert_eq!(path, store.data_dir.join("Test (3).odict"));
+		let odict = read_odict_from_path(&path).unwrap();
+		assert_eq!(odict.name.as_deref(), Some("Test (3)"));
+		assert_eq!(terms(&path), ["new"]);
+		assert_eq!(
+			files(&store.data_dir),
+			["Test (2).odict", "Test (3).odict", "Test.odict"]
+		);
+
+		let replaced = conflict(&dir, &store, "replaced.odict");
+		let path = replaced.resolve(ConflictResolution::Replace).unwrap();
+		assert_eq!(path, Some(existing.clone()));
+		assert_eq!(terms(&existing), ["new"]);
+		assert_eq!(files(&store.data_dir).len(), 3);
+
+		// already resolved
+		assert!(matches!(
+			replaced.resolve(ConflictResolution::Skip),
+			Err(Error::Io(_))
+		));
+	}
+}
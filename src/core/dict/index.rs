@@ -0,0 +1,954 @@
+//! A compact and immutable index of terms.
+//!
+//! Terms are stored in a minimal acyclic automaton, i.e. a trie whose identical
+//! sub-tries are merged, so common suffixes like "-tion" are only stored once. Each
+//! state also knows how many terms can be accepted from it, which maps every term to
+//! its ordinal in lexicographic order and back, like a finite state transducer does.
+
+use super::cache::{put_bytes, put_u32s, take_bytes, take_u32s};
+use super::{Error, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+struct Automaton {
+	/// Transitions of state `i` are in `starts[i]..starts[i + 1]`, sorted by label.
+	starts: Vec<u32>,
+	/// Number of terms accepted from each state.
+	counts: Vec<u32>,
+	finals: Vec<bool>,
+	labels: Vec<u8>,
+	targets: Vec<u32>,
+}
+
+impl Automaton {
+	fn root(&self) -> usize {
+		self.counts.len() - 1
+	}
+
+	fn transitions(&self, state: usize) -> std::ops::Range<usize> {
+		self.starts[state] as usize..self.starts[state + 1] as usize
+	}
+
+	fn count(&self, state: usize) -> usize {
+		self.counts[state] as usize
+	}
+
+	fn target(&self, transition: usize) -> usize {
+		self.targets[transition] as usize
+	}
+
+	/// Whether the arrays are consistent, states only lead to states compiled before
+	/// them so it's acyclic, and transitions of each state are sorted by unique labels.
+	fn is_valid(&self) -> bool {
+		let states = self.counts.len();
+		if states == 0
+			|| self.starts.len() != states + 1
+			|| self.finals.len() != states
+			|| self.labels.len() != self.targets.len()
+			|| self.starts.last().map(|n| *n as usize) != Some(self.labels.len())
+			|| !self.starts.is_sorted()
+		{
+			return false;
+		}
+
+		(0..states).all(|state| {
+			let transitions = self.transitions(state);
+			if !self.labels[transitions.clone()]
+				.windows(2)
+				.all(|pair| pair[0] < pair[1])
+			{
+				return false;
+			}
+			let mut count = usize::from(self.finals[state]);
+			for t in transitions {
+				let target = self.target(t);
+				if target >= state {
+					return false;
+				}
+				count += self.count(target);
+			}
+			count == self.count(state)
+		})
+	}
+
+	/// Follow the transition labeled `label` from `state`.
+	fn next(&self, state: usize, label: u8) -> Option<usize> {
+		let range = self.transitions(state);
+		let start = range.start;
+		self.labels[range]
+			.binary_search(&label)
+			.ok()
+			.map(|i| self.target(start + i))
+	}
+}
+
+/// A matcher fed with bytes of terms, which lets the index skip terms that could not
+/// match as early as possible.
+pub trait Matcher {
+	type State;
+
+	fn start(&self) -> Self::State;
+
+	/// Returns the state after `byte`, `None` if no term continuing with it could match.
+	fn accept(&self, state: &Self::State, byte: u8) -> Option<Self::State>;
+
+	fn is_match(&self, state: &Self::State) -> bool;
+}
+
+/// The index of sorted and unique terms, cloning it is cheap.
+#[derive(Debug, Clone)]
+pub struct TermIndex {
+	automaton: Arc<Automaton>,
+}
+
+impl TermIndex {
+	/// Build the index from `terms`, which must be sorted, duplicates are ignored.
+	///
+	/// # Panics
+	///
+	/// Will panic if `terms` is not sorted
+	pub fn from_sorted<S: AsRef<str>>(terms: impl IntoIterator<Item = S>) -> Self {
+		let mut builder = Builder::default();
+		for term in terms {
+			builder.insert(term.as_ref().as_bytes());
+		}
+
+		builder.finish()
+	}
+
+	/// Number of terms.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.automaton.count(self.automaton.root())
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Number of bytes used by the index.
+	#[must_use]
+	pub fn heap_size(&self) -> usize {
+		let automaton = &self.automaton;
+		(automaton.starts.len() + automaton.counts.len() + automaton.targets.len()) * 4
+			+ automaton.finals.len()
+			+ automaton.labels.len()
+	}
+
+	/// Returns the ordinal of `s`, `None` if it's not in the index.
+	#[must_use]
+	pub fn ordinal(&self, s: &str) -> Option<usize> {
+		let (rank, found) = self.rank(s.as_bytes());
+		found.then_some(rank)
+	}
+
+	#[must_use]
+	pub fn contains(&self, s: &str) -> bool {
+		self.ordinal(s).is_some()
+	}
+
+	/// Returns the term at `ordinal`, `None` if it's out of range.
+	#[must_use]
+	pub fn get(&self, ordinal: usize) -> Option<String> {
+		let mut terms = self.terms_from(ordinal);
+		terms.next()
+	}
+
+	/// Returns all terms in lexicographic order.
+	#[must_use]
+	pub fn iter(&self) -> Terms<'_> {
+		self.terms_from(0)
+	}
+
+	/// Returns terms starting with `prefix`.
+	#[must_use]
+	pub fn prefix(&self, prefix: &str) -> Terms<'_> {
+		let automaton = &self.automaton;
+		let mut state = automaton.root();
+		for byte in prefix.as_bytes() {
+			match automaton.next(state, *byte) {
+				Some(next) => state = next,
+				None => return Terms::empty(self),
+			}
+		}
+
+		let (start, _) = self.rank(prefix.as_bytes());
+		let mut terms = self.terms_from(start);
+		terms.remaining = automaton.count(state);
+		terms
+	}
+
+	/// Returns terms in `start..end`, either bound could be `None` to be unbounded.
+	#[must_use]
+	pub fn range(&self, start: Option<&str>, end: Option<&str>) -> Terms<'_> {
+		let start = start.map_or(0, |s| self.rank(s.as_bytes()).0);
+		let end = end.map_or(self.len(), |s| self.rank(s.as_bytes()).0);
+
+		let mut terms = self.terms_from(start);
+		terms.remaining = end.saturating_sub(start);
+		terms
+	}
+
+	/// Returns at most `limit` terms matched by `matcher` with their ordinals, in
+	/// lexicographic order.
+	#[must_use]
+	pub fn search<M: Matcher>(&self, matcher: &M, limit: usize) -> Vec<(usize, String)> {
+		let mut search = MatcherSearch {
+			automaton: &self.automaton,
+			matcher,
+			limit,
+			buffer: Vec::new(),
+			result: Vec::new(),
+		};
+		search.walk(self.automaton.root(), &matcher.start(), 0);
+
+		search.result
+	}
+
+	/// Returns the number of terms less than `s`, and whether `s` is in the index.
+	fn rank(&self, s: &[u8]) -> (usize, bool) {
+		let automaton = &self.automaton;
+		let mut state = automaton.root();
+		let mut rank = 0;
+		for byte in s {
+			// the term ending here and smaller siblings come first
+			rank += usize::from(automaton.finals[state]);
+			let mut next = None;
+			for t in automaton.transitions(state) {
+				if automaton.labels[t] < *byte {
+					rank += automaton.count(automaton.target(t));
+				} else {
+					if automaton.labels[t] == *byte {
+						next = Some(automaton.target(t));
+					}
+					break;
+				}
+			}
+
+			match next {
+				Some(next) => state = next,
+				None => return (rank, false),
+			}
+		}
+
+		(rank, automaton.finals[state])
+	}
+
+	/// Returns terms starting from the one at `ordinal`.
+	fn terms_from(&self, mut ordinal: usize) -> Terms<'_> {
+		if ordinal >= self.len() {
+			return Terms::empty(self);
+		}
+
+		let automaton = &self.automaton;
+		let mut terms = Terms {
+			index: self,
+			stack: Vec::new(),
+			buffer: Vec::new(),
+			pending: true,
+			ordinal,
+			remaining: self.len() - ordinal,
+		};
+		let mut state = automaton.root();
+		'descend: loop {
+			if automaton.finals[state] {
+				if ordinal == 0 {
+					terms
+						.stack
+						.push((state, automaton.transitions(state).start));
+					break;
+				}
+				ordinal -= 1;
+			}
+
+			for t in automaton.transitions(state) {
+				let target = automaton.target(t);
+				if ordinal < automaton.count(target) {
+					terms.stack.push((state, t + 1));
+					terms.buffer.push(automaton.labels[t]);
+					state = target;
+					continue 'descend;
+				}
+				ordinal -= automaton.count(target);
+			}
+
+			unreachable!("ordinal is less than the count of the state");
+		}
+
+		terms
+	}
+
+	/// Returns at most `limit` terms whose edit distance to `s` is not greater than
+	/// `max_distance`, ranked by distance and then lexicographically.
+	///
+	/// The distance is the optimal string alignment distance over chars, i.e. the
+	/// Levenshtein distance where swapping two adjacent chars counts as one edit.
+	#[must_use]
+	pub fn suggest(&self, s: &str, max_distance: usize, limit: usize) -> Vec<(String, usize)> {
+		let target: Vec<char> = s.chars().collect();
+		let row: Vec<usize> = (0..=target.len()).collect();
+		let mut state = SuggestState {
+			automaton: &self.automaton,
+			target: &target,
+			max_distance,
+			buffer: Vec::new(),
+			result: Vec::new(),
+		};
+		state.walk(self.automaton.root(), 0, &row, None);
+
+		state
+			.result
+			.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+		state.result.truncate(limit);
+		state.result
+	}
+
+	/// Serialize the index, which could be read back by [`Self::read_from`].
+	pub(crate) fn write_to(&self, buf: &mut Vec<u8>) {
+		let automaton = &self.automaton;
+		for array in [&automaton.starts, &automaton.counts, &automaton.targets] {
+			put_u32s(buf, array);
+		}
+		let finals: Vec<u8> = automaton
+			.finals
+			.iter()
+			.map(|is_final| u8::from(*is_final))
+			.collect();
+		put_bytes(buf, &finals);
+		put_bytes(buf, &automaton.labels);
+	}
+
+	/// Deserialize an index written by [`Self::write_to`], advancing `bytes`.
+	pub(crate) fn read_from(bytes: &mut &[u8]) -> Result<Self> {
+		let starts = take_u32s(bytes)?;
+		let counts = take_u32s(bytes)?;
+		let targets = take_u32s(bytes)?;
+		let finals = take_bytes(bytes)?.iter().map(|b| *b != 0).collect();
+		let labels = take_bytes(bytes)?.to_vec();
+
+		let automaton = Automaton {
+			starts,
+			counts,
+			finals,
+			labels,
+			targets,
+		};
+		if !automaton.is_valid() {
+			return Err(Error::format("invalid term index"));
+		}
+
+		Ok(Self {
+			automaton: Arc::new(automaton),
+		})
+	}
+}
+
+impl Default for TermIndex {
+	fn default() -> Self {
+		Builder::default().finish()
+	}
+}
+
+impl<'a> IntoIterator for &'a TermIndex {
+	type Item = String;
+	type IntoIter = Terms<'a>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+/// Iterator over terms of a [`TermIndex`] in lexicographic order.
+#[derive(Debug, Clone)]
+pub struct Terms<'a> {
+	index: &'a TermIndex,
+	/// The path to the current state, with the next transition to follow of each state.
+	stack: Vec<(usize, usize)>,
+	buffer: Vec<u8>,
+	/// Whether the current state is final and not yielded yet.
+	pending: bool,
+	/// Ordinal of the next term.
+	ordinal: usize,
+	remaining: usize,
+}
+
+impl<'a> Terms<'a> {
+	fn empty(index: &'a TermIndex) -> Self {
+		Self {
+			index,
+			stack: Vec::new(),
+			buffer: Vec::new(),
+			pending: false,
+			ordinal: index.len(),
+			remaining: 0,
+		}
+	}
+
+	/// Returns the ordinal of the next term.
+	#[must_use]
+	pub fn ordinal(&self) -> usize {
+		self.ordinal
+	}
+
+	/// Pair each term with its ordinal.
+	pub fn with_ordinals(self) -> impl Iterator<Item = (usize, String)> + 'a {
+		(self.ordinal..).zip(self)
+	}
+
+	fn yield_term(&mut self) -> String {
+		self.ordinal += 1;
+		self.remaining -= 1;
+		String::from_utf8_lossy(&self.buffer).into_owned()
+	}
+}
+
+impl Iterator for Terms<'_> {
+	type Item = String;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.remaining == 0 {
+			return None;
+		}
+		if self.pending {
+			self.pending = false;
+			return Some(self.yield_term());
+		}
+
+		let automaton = &self.index.automaton;
+		while let Some((state, t)) = self.stack.last_mut() {
+			if *t < automaton.transitions(*state).end {
+				let transition = *t;
+				*t += 1;
+				let target = automaton.target(transition);
+				self.buffer.push(automaton.labels[transition]);
+				self.stack
+					.push((target, automaton.transitions(target).start));
+				if automaton.finals[target] {
+					return Some(self.yield_term());
+				}
+			} else {
+				self.stack.pop();
+				self.buffer.pop();
+			}
+		}
+
+		None
+	}
+
+	/// Skip `n` terms by seeking to the ordinal directly, which makes `skip` cheap.
+	fn nth(&mut self, n: usize) -> Option<Self::Item> {
+		if n >= self.remaining {
+			self.remaining = 0;
+			return None;
+		}
+
+		let remaining = self.remaining - n;
+		*self = self.index.terms_from(self.ordinal + n);
+		self.remaining = remaining;
+		self.next()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining, Some(self.remaining))
+	}
+}
+
+impl ExactSizeIterator for Terms<'_> {}
+
+/// Builds the minimal automaton from sorted terms, see "Incremental Construction of
+/// Minimal Acyclic Finite-State Automata" by Daciuk et al.
+#[derive(Default)]
+struct Builder {
+	automaton: Automaton,
+	/// Compiled states by their finality and transitions, to merge identical states.
+	registry: HashMap<(bool, Vec<(u8, u32)>), u32>,
+	/// States of the last term which could still change, the target of the last
+	/// transition of each state is the next state in the stack.
+	unfinished: Vec<(bool, Vec<(u8, u32)>)>,
+	last: Vec<u8>,
+}
+
+impl Builder {
+	fn insert(&mut self, term: &[u8]) {
+		if self.unfinished.is_empty() {
+			self.unfinished.push((false, Vec::new()));
+		} else if term == self.last.as_slice() {
+			return;
+		} else {
+			assert!(term > self.last.as_slice(), "terms are not sorted");
+		}
+
+		let common = term
+			.iter()
+			.zip(&self.last)
+			.take_while(|(a, b)| a == b)
+			.count();
+		self.compile_until(common + 1);
+
+		for byte in &term[common..] {
+			if let Some((_, transitions)) = self.unfinished.last_mut() {
+				transitions.push((*byte, u32::MAX));
+			}
+			self.unfinished.push((false, Vec::new()));
+		}
+		if let Some((is_final, _)) = self.unfinished.last_mut() {
+			*is_final = true;
+		}
+
+		self.last = term.to_vec();
+	}
+
+	/// Compile unfinished states until there are only `len` of them.
+	fn compile_until(&mut self, len: usize) {
+		while self.unfinished.len() > len {
+			let Some(state) = self.unfinished.pop() else {
+				break;
+			};
+			let id = self.compile(state);
+			if let Some((_, transitions)) = self.unfinished.last_mut()
+				&& let Some(last) = transitions.last_mut()
+			{
+				last.1 = id;
+			}
+		}
+	}
+
+	fn compile(&mut self, state: (bool, Vec<(u8, u32)>)) -> u32 {
+		if let Some(id) = self.registry.get(&state) {
+			return *id;
+		}
+
+		let automaton = &mut self.automaton;
+		if automaton.starts.is_empty() {
+			automaton.starts.push(0);
+		}
+		let id = u32::try_from(automaton.counts.len()).expect("too many states");
+		let (is_final, transitions) = &state;
+		let mut count = u32::from(*is_final);
+		for (label, target) in transitions {
+			count += automaton.counts[*target as usize];
+			automaton.labels.push(*label);
+			automaton.targets.push(*target);
+		}
+		automaton.counts.push(count);
+		automaton.finals.push(*is_final);
+		automaton
+			.starts
+			.push(u32::try_from(automaton.labels.len()).expect("too many transitions"));
+
+		self.registry.insert(state, id);
+		id
+	}
+
+	fn finish(mut self) -> TermIndex {
+		self.compile_until(1);
+		// the root is always compiled last, even if it's identical to another state
+		let root = self.unfinished.pop().unwrap_or_default();
+		self.registry.clear();
+		self.compile(root);
+
+		TermIndex {
+			automaton: Arc::new(self.automaton),
+		}
+	}
+}
+
+struct MatcherSearch<'a, M: Matcher> {
+	automaton: &'a Automaton,
+	matcher: &'a M,
+	limit: usize,
+	buffer: Vec<u8>,
+	result: Vec<(usize, String)>,
+}
+
+impl<M: Matcher> MatcherSearch<'_, M> {
+	/// Walks terms accepted from `state`, the first of which is at `ordinal`.
+	fn walk(&mut self, state: usize, matcher_state: &M::State, mut ordinal: usize) {
+		let automaton = self.automaton;
+		if automaton.finals[state] {
+			if self.matcher.is_match(matcher_state) {
+				self.result
+					.push((ordinal, String::from_utf8_lossy(&self.buffer).into_owned()));
+			}
+			ordinal += 1;
+		}
+
+		for t in automaton.transitions(state) {
+			if self.result.len() >= self.limit {
+				return;
+			}
+
+			let target = automaton.target(t);
+			if let Some(next) = self.matcher.accept(matcher_state, automaton.labels[t]) {
+				self.buffer.push(automaton.labels[t]);
+				self.walk(target, &next, ordinal);
+				self.buffer.pop();
+			}
+			ordinal += automaton.count(target);
+		}
+	}
+}
+
+struct SuggestState<'a> {
+	automaton: &'a Automaton,
+	target: &'a [char],
+	max_distance: usize,
+	buffer: Vec<u8>,
+	result: Vec<(String, usize)>,
+}
+
+impl SuggestState<'_> {
+	/// Walks the automaton while computing the distance matrix one char at a time.
+	///
+	/// `char_start` is where the current (maybe incomplete) UTF-8 char begins in the
+	/// buffer, `row` is the matrix row of the last complete char and `prev` holds the
+	/// row and char before it, which transpositions need.
+	fn walk(
+		&mut self,
+		state: usize,
+		char_start: usize,
+		row: &[usize],
+		prev: Option<(&[usize], char)>,
+	) {
+		let automaton = self.automaton;
+		for t in automaton.transitions(state) {
+			let next = automaton.target(t);
+			self.buffer.push(automaton.labels[t]);
+
+			let Ok(c) = std::str::from_utf8(&self.buffer[char_start..]) else {
+				// not a complete char yet, keep walking with the same rows
+				self.walk(next, char_start, row, prev);
+				self.buffer.pop();
+				continue;
+			};
+			let c = c.chars().next().unwrap_or_default();
+
+			let target = self.target;
+			let mut current = Vec::with_capacity(row.len());
+			current.push(row[0] + 1);
+			for j in 1..row.len() {
+				let cost = usize::from(target[j - 1] != c);
+				let mut distance = (row[j] + 1).min(current[j - 1] + 1).min(row[j - 1] + cost);
+				if let Some((prev_row, prev_char)) = prev
+					&& j > 1 && target[j - 1] == prev_char
+					&& target[j - 2] == c
+				{
+					distance = distance.min(prev_row[j - 2] + 1);
+				}
+				current.push(distance);
+			}
+
+			let distance = current[current.len() - 1];
+			if automaton.finals[next] && distance <= self.max_distance {
+				self.result
+					.push((String::from_utf8_lossy(&self.buffer).into_owned(), distance));
+			}
+
+			// a transposition in the next row can still lower the distance by one
+			let lower_bound = current
+				.iter()
+				.min()
+				.copied()
+				.unwrap_or_default()
+				.min(row.iter().min().copied().unwrap_or_default() + 1);
+			if lower_bound <= self.max_distance {
+				let char_end = self.buffer.len();
+				self.walk(next, char_end, &current, Some((row, c)));
+			}
+
+			self.buffer.pop();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TERMS: [&str; 7] = ["a", "abc", "abd", "b", "bcd", "nation", "station"];
+
+	fn written(index: &TermIndex) -> Vec<u8> {
+		let mut bytes = Vec::new();
+		index.write_to(&mut bytes);
+		bytes
+	}
+
+	/// Use every method which walks the automaton.
+	fn check_usable(index: &TermIndex) {
+		let terms: Vec<_> = index.iter().collect();
+		assert_eq!(terms.len(), index.len());
+		for (i, term) in terms.iter().enumerate() {
+			assert_eq!(index.get(i).as_ref(), Some(term));
+			// invalid UTF-8 from garbage is replaced, so the term is not found
+			if !term.contains(char::REPLACEMENT_CHARACTER) {
+				assert_eq!(index.ordinal(term), Some(i), "{term:?}");
+			}
+		}
+		assert_eq!(index.get(terms.len()), None);
+		for prefix in ["", "a", "ab", "x"] {
+			_ = index.prefix(prefix).count();
+		}
+		_ = index.iter().nth(2);
+		_ = index.suggest("abe", 1, 10);
+	}
+
+	/// Terms of mixed lengths, with the empty term, shared suffixes and non-ASCII chars.
+	const MIXED: [&str; 14] = [
+		"",
+		"a",
+		"ab",
+		"abc",
+		"abd",
+		"b",
+		"café",
+		"cafés",
+		"nation",
+		"relation",
+		"station",
+		"zebra",
+		"日本",
+		"日本語",
+	];
+
+	/// Strings which are not in [`MIXED`], to probe between its terms.
+	const ABSENT: [&str; 8] = ["aa", "abcd", "ac", "caf", "cafe", "n", "zz", "日"];
+
+	/// Number of states of a trie of `terms`, i.e. of their distinct prefixes.
+	fn trie_states(terms: &[&str]) -> usize {
+		let prefixes: std::collections::BTreeSet<&[u8]> = terms
+			.iter()
+			.flat_map(|term| (0..=term.len()).map(|i| &term.as_bytes()[..i]))
+			.collect();
+		prefixes.len()
+	}
+
+	#[test]
+	fn same_as_sorted_vec() {
+		let index = TermIndex::from_sorted(MIXED);
+		let sorted: Vec<String> = MIXED.iter().map(ToString::to_string).collect();
+		assert!(sorted.is_sorted());
+
+		assert_eq!(index.len(), sorted.len());
+		assert_eq!(index.iter().collect::<Vec<_>>(), sorted);
+		for (i, term) in sorted.iter().enumerate() {
+			assert_eq!(index.get(i).as_ref(), Some(term));
+			assert_eq!(index.ordinal(term), Some(i), "{term:?}");
+			assert_eq!(index.iter().nth(i).as_ref(), Some(term));
+			assert_eq!(
+				index.iter().skip(i).collect::<Vec<_>>(),
+				sorted[i..],
+				"skip {i}"
+			);
+		}
+		assert_eq!(index.get(sorted.len()), None);
+		assert_eq!(index.iter().nth(sorted.len()), None);
+		for absent in ABSENT {
+			assert!(!index.contains(absent), "{absent:?}");
+		}
+
+		let probes: Vec<&str> = MIXED.iter().chain(&ABSENT).copied().collect();
+		for prefix in MIXED
+			.iter()
+			.flat_map(|term| term.char_indices().map(|(i, _)| &term[..i]))
+			.chain(probes.iter().copied())
+		{
+			let expected: Vec<_> = sorted
+				.iter()
+				.filter(|s| s.starts_with(prefix))
+				.cloned()
+				.collect();
+			let terms = index.prefix(prefix);
+			assert_eq!(terms.len(), expected.len(), "{prefix:?}");
+			let first = sorted.iter().position(|s| s.starts_with(prefix));
+			if let Some(first) = first {
+				assert_eq!(terms.ordinal(), first, "{prefix:?}");
+			}
+			assert_eq!(terms.collect::<Vec<_>>(), expected, "{prefix:?}");
+		}
+
+		for start in &probes {
+			for end in &probes {
+				let expected: Vec<_> = sorted
+					.iter()
+					.filter(|s| s.as_str() >= *start && s.as_str() < *end)
+					.cloned()
+					.collect();
+				let terms = index.range(Some(start), Some(end));
+				assert_eq!(terms.collect::<Vec<_>>(), expected, "{start:?}..{end:?}");
+			}
+			let expected: Vec<_> = sorted
+				.iter()
+				.filter(|s| s.as_str() >= *start)
+				.cloned()
+				.collect();
+			assert_eq!(index.range(Some(start), None).collect::<Vec<_>>(), expected);
+			let expected: Vec<_> = sorted
+				.iter()
+				.filter(|s| s.as_str() < *start)
+				.cloned()
+				.collect();
+			assert_eq!(index.range(None, Some(start)).collect::<Vec<_>>(), expected);
+		}
+		assert_eq!(index.range(None, None).collect::<Vec<_>>(), sorted);
+	}
+
+	#[test]
+	fn with_ordinals() {
+		let index = TermIndex::from_sorted(MIXED);
+
+		let pairs: Vec<_> = index.prefix("ab").with_ordinals().collect();
+		assert_eq!(
+			pairs,
+			[
+				(2, "ab".to_string()),
+				(3, "abc".to_string()),
+				(4, "abd".to_string())
+			]
+		);
+	}
+
+	#[test]
+	fn minimized() {
+		let index = TermIndex::from_sorted(["nation", "station"]);
+		// the root, "s" and the states of "tion", "ion", "on", "n" and the end, where
+		// "st" is merged with "n" because both lead to "ation"
+		assert_eq!(index.automaton.counts.len(), 8);
+
+		for terms in [&TERMS[..], &MIXED[..]] {
+			let index = TermIndex::from_sorted(terms);
+			assert!(index.automaton.is_valid());
+			assert!(index.automaton.counts.len() < trie_states(terms));
+		}
+	}
+
+	#[test]
+	fn duplicates_ignored() {
+		let index = TermIndex::from_sorted(["a", "a", "b", "b", "b"]);
+
+		assert_eq!(index.iter().collect::<Vec<_>>(), ["a", "b"]);
+	}
+
+	#[test]
+	#[should_panic(expected = "terms are not sorted")]
+	fn unsorted_rejected() {
+		_ = TermIndex::from_sorted(["b", "a"]);
+	}
+
+	#[test]
+	fn empty() {
+		let index = TermIndex::default();
+
+		assert!(index.is_empty());
+		assert_eq!(index.get(0), None);
+		assert_eq!(index.prefix("").count(), 0);
+		assert_eq!(index.range(None, None).count(), 0);
+
+		let index = TermIndex::from_sorted([""]);
+		assert_eq!(index.len(), 1);
+		assert_eq!(index.ordinal(""), Some(0));
+		assert_eq!(index.prefix("").collect::<Vec<_>>(), [""]);
+	}
+
+	/// Pair each term with its distance.
+	fn suggestions<const N: usize>(pairs: [(&str, usize); N]) -> Vec<(String, usize)> {
+		pairs
+			.into_iter()
+			.map(|(term, distance)| (term.to_string(), distance))
+			.collect()
+	}
+
+	#[test]
+	fn suggest() {
+		let index = TermIndex::from_sorted(["believe", "receive", "recipe", "relieve"]);
+
+		// swapping "ie" is one edit, while it's two in the Levenshtein distance
+		assert_eq!(
+			index.suggest("recieve", 1, 10),
+			suggestions([("receive", 1), ("relieve", 1)])
+		);
+		assert_eq!(
+			index.suggest("receive", 0, 10),
+			suggestions([("receive", 0)])
+		);
+		assert_eq!(index.suggest("xyz", 2, 10), suggestions([]));
+	}
+
+	#[test]
+	fn suggest_ranked() {
+		let index = TermIndex::from_sorted(["believe", "receive", "recipe", "relieve"]);
+
+		// by distance, then lexicographically
+		assert_eq!(
+			index.suggest("recieve", 2, 10),
+			suggestions([
+				("receive", 1),
+				("relieve", 1),
+				("believe", 2),
+				("recipe", 2)
+			])
+		);
+		assert_eq!(
+			index.suggest("recieve", 2, 3),
+			suggestions([("receive", 1), ("relieve", 1), ("believe", 2)])
+		);
+		assert_eq!(index.suggest("recieve", 2, 0), suggestions([]));
+	}
+
+	#[test]
+	fn suggest_chars() {
+		let index = TermIndex::from_sorted(["a", "ab", "b", "café", "日本"]);
+
+		// distances are counted in chars rather than bytes
+		assert_eq!(index.suggest("cafe", 1, 10), suggestions([("café", 1)]));
+		assert_eq!(index.suggest("本日", 1, 10), suggestions([("日本", 1)]));
+
+		assert_eq!(index.suggest("", 0, 10), suggestions([]));
+		assert_eq!(index.suggest("", 1, 10), suggestions([("a", 1), ("b", 1)]));
+	}
+
+	#[test]
+	fn read_written() {
+		let index = TermIndex::from_sorted(TERMS);
+		let bytes = written(&index);
+
+		let mut rest = bytes.as_slice();
+		let read = TermIndex::read_from(&mut rest).unwrap();
+		assert!(rest.is_empty());
+		assert_eq!(read.iter().collect::<Vec<_>>(), TERMS);
+		check_usable(&read);
+	}
+
+	#[test]
+	fn read_truncated() {
+		let bytes = written(&TermIndex::from_sorted(TERMS));
+
+		for len in 0..bytes.len() {
+			let result = TermIndex::read_from(&mut &bytes[..len]);
+			assert!(matches!(result, Err(Error::Format(_))), "{len}: {result:?}");
+		}
+	}
+
+	#[test]
+	fn read_garbage() {
+		let bytes = written(&TermIndex::from_sorted(TERMS));
+
+		// an index read from corrupted bytes must be rejected, or work without panics
+		for i in 0..bytes.len() {
+			for mask in [0x01, 0x02, 0x10, 0x80, 0xff] {
+				let mut garbage = bytes.clone();
+				garbage[i] ^= mask;
+				match TermIndex::read_from(&mut garbage.as_slice()) {
+					Ok(index) => check_usable(&index),
+					Err(err) => assert!(matches!(err, Error::Format(_)), "{err:?}"),
+				}
+			}
+		}
+
+		for garbage in [vec![0; 64], vec![0xff; 64], b"not an index at all".to_vec()] {
+			let result = TermIndex::read_from(&mut garbage.as_slice());
+			assert!(matches!(result, Err(Error::Format(_))), "{result:?}");
+		}
+	}
+}
@@ -1,8 +1,9 @@
 use tracing::warn;
 
-use super::Dictionary;
+use super::{Dictionary, Error, Result};
 use std::path::PathBuf;
 
+#[derive(Debug, Clone)]
 pub struct LazyDict {
 	pub path: PathBuf,
 	dictionary: Option<Dictionary>,
@@ -32,32 +33,63 @@ impl LazyDict {
 		self.dictionary = Some(dictionary);
 	}
 
+	/// Returns the dictionary if it's loaded.
+	#[must_use]
+	pub fn dictionary(&self) -> Option<&Dictionary> {
+		self.dictionary.as_ref()
+	}
+
+	/// Search at most `limit` terms starting with `s`, skipping the first `offset` ones.
+	///
 	/// # Errors
 	///
 	/// Will return `Err` if dictionary is not loaded
-	pub fn search(&self, s: &str) -> anyhow::Result<Vec<String>> {
+	pub fn search(&self, s: &str, offset: usize, limit: usize) -> Result<Vec<String>> {
 		match &self.dictionary {
-			Some(dict) => Ok(dict.trie.search(s)),
-			None => Err(anyhow::anyhow!("dictionary {:?} is not loaded", self.path)),
+			Some(dict) => Ok(dict.search(s, offset, limit)),
+			None => Err(Error::NotLoaded(self.path.clone())),
 		}
 	}
 
+	/// Suggest at most `limit` terms which are close to `s`, nearest first.
+	///
 	/// # Errors
 	///
 	/// Will return `Err` if dictionary is not loaded
-	pub fn get(&self, s: &str) -> anyhow::Result<Option<&odict::Entry>> {
+	pub fn suggest(&self, s: &str, limit: usize) -> Result<Vec<String>> {
+		// allow more typos in longer words
+		let max_distance = match s.chars().count() {
+			0..=4 => 1,
+			5..=8 => 2,
+			_ => 3,
+		};
+
 		match &self.dictionary {
-			Some(dict) => Ok(dict.odict.entries.get(s)),
-			None => Err(anyhow::anyhow!("dictionary {:?} is not loaded", self.path)),
+			Some(dict) => Ok(dict.suggest(s, max_distance, limit)),
+			None => Err(Error::NotLoaded(self.path.clone())),
 		}
 	}
 
-	/// # Panics
+	/// # Errors
 	///
-	/// Will panic if `self.path` is not valid
+	/// Will return `Err` if dictionary is not loaded
+	pub fn get(&self, s: &str) -> Result<Option<&odict::Entry>> {
+		match &self.dictionary {
+			Some(dict) => Ok(dict.get(s)),
+			None => Err(Error::NotLoaded(self.path.clone())),
+		}
+	}
+
+	/// Returns the name of the dictionary, or its file stem if it has none or it's not
+	/// loaded. Invalid unicode in the file stem is replaced.
 	#[must_use]
 	pub fn name(&self) -> String {
-		let stem = self.path.file_stem().unwrap().to_str().unwrap().to_owned();
+		let stem = self
+			.path
+			.file_stem()
+			.unwrap_or(self.path.as_os_str())
+			.to_string_lossy()
+			.into_owned();
 
 		if let Some(dict) = &self.dictionary {
 			dict.odict.name.clone().unwrap_or(stem)
@@ -66,3 +98,77 @@ impl LazyDict {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn loaded(terms: &[&str]) -> LazyDict {
+		let mut odict = odict::Dictionary::default();
+		for term in terms {
+			odict.entries.insert(odict::Entry {
+				term: (*term).to_string(),
+				..Default::default()
+			});
+		}
+		let mut dict = LazyDict::new(PathBuf::from("test.odict"));
+		dict.load(Dictionary::new(odict));
+
+		dict
+	}
+
+	#[test]
+	fn suggest() {
+		let dict = loaded(&["Café", "cat", "dictionary", "receive", "relieve"]);
+
+		assert_eq!(dict.suggest("recieve", 10).unwrap(), ["receive", "relieve"]);
+		assert_eq!(dict.suggest("recieve", 1).unwrap(), ["receive"]);
+		// originals of folded terms are suggested
+		assert_eq!(dict.suggest("cafes", 10).unwrap(), ["Café"]);
+		assert!(dict.suggest("", 10).unwrap().is_empty());
+	}
+
+	#[test]
+	fn suggest_more_typos_in_longer_words() {
+		let dict = loaded(&["cat", "dictionary"]);
+
+		assert_eq!(dict.suggest("ct", 10).unwrap(), ["cat"]);
+		assert_eq!(dict.suggest("cta", 10).unwrap(), ["cat"]);
+		assert!(dict.suggest("c", 10).unwrap().is_empty());
+		assert_eq!(dict.suggest("dictonry", 10).unwrap(), ["dictionary"]);
+		assert_eq!(dict.suggest("dicionry", 10).unwrap(), ["dictionary"]);
+		assert!(dict.suggest("dictry", 10).unwrap().is_empty());
+	}
+
+	#[test]
+	fn suggest_not_loaded() {
+		let dict = LazyDict::new(PathBuf::from("test.odict"));
+
+		assert!(matches!(dict.suggest("cat", 10), Err(Error::NotLoaded(_))));
+	}
+
+	#[test]
+	fn name_of_file_stem() {
+		assert_eq!(
+			LazyDict::new(PathBuf::from("/dicts/English.odict")).name(),
+			"English"
+		);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn name_of_non_unicode_stem() {
+		use std::ffi::OsStr;
+		use std::os::unix::ffi::OsStrExt;
+
+		let path = PathBuf::from(OsStr::from_bytes(b"/dicts/Fran\xe7ais.odict"));
+		assert_eq!(LazyDict::new(path).name(), "Fran\u{fffd}ais");
+	}
+
+	#[test]
+	fn name_without_file_stem() {
+		assert_eq!(LazyDict::new(PathBuf::from("/")).name(), "/");
+		assert_eq!(LazyDict::new(PathBuf::from("..")).name(), "..");
+		assert_eq!(LazyDict::new(PathBuf::new()).name(), "");
+	}
+}
@@ -1,8 +1,9 @@
-use crate::{app::AppModel, elapsed_secs, now};
+use super::{Error, Result, read_stardict_from_path};
+use crate::{write_atomically, write_new_atomically};
 use odict::semver::SemanticVersion;
 use std::path::{Path, PathBuf};
-use tracing::{info, info_span};
-use url::Url;
+use std::{fs, io};
+use tracing::info;
 
 pub const MINIMAL_ODICT_VERSION: SemanticVersion = SemanticVersion {
 	major: 2,
@@ -20,92 +21,217 @@ pub fn is_odict_file_compatible(file: &odict::DictionaryFile) -> bool {
 /// # Errors
 ///
 /// Will return `Err` if `path` or the format not valid
-pub fn read_odict_file_from_path(path: &Path) -> anyhow::Result<odict::DictionaryFile> {
+pub fn read_odict_file_from_path(path: &Path) -> Result<odict::DictionaryFile> {
 	odict::DictionaryReader::new()
 		.read_from_path(
 			path.to_str()
-				.ok_or(anyhow::anyhow!("path is not valid unicode: {path:?}"))?,
+				.ok_or_else(|| Error::InvalidPath(path.to_path_buf()))?,
 		)
-		.map_err(|err| anyhow::anyhow!(err))
+		.map_err(Error::odict)
 }
 
 /// # Errors
 ///
 /// Will return `Err` if file format not valid or version not compatible
-pub fn read_odict_from_path(path: &Path) -> anyhow::Result<odict::Dictionary> {
+pub fn read_odict_from_path(path: &Path) -> Result<odict::Dictionary> {
 	let odict_file = read_odict_file_from_path(path)?;
 	if !is_odict_file_compatible(&odict_file) {
-		anyhow::bail!(
-			"require ODict version ~{MINIMAL_ODICT_VERSION}, but found {}",
-			odict_file.version
-		)
+		return Err(Error::IncompatibleVersion {
+			found: odict_file.version,
+			required: MINIMAL_ODICT_VERSION,
+		});
 	}
 
-	odict_file
-		.to_dictionary()
-		.map_err(|err| anyhow::anyhow!(err))
+	odict_file.to_dictionary().map_err(Error::odict)
 }
 
+/// Read a dictionary of any supported format, converting it to `ODict`.
+///
+/// `StarDict` dictionaries are recognized by their `.ifo` file, other files are read as
+/// `ODict`.
+///
 /// # Errors
 ///
-/// Will return `Err` if write failed
-pub fn write_odict_to_path(dictionary: &odict::Dictionary, path: &Path) -> anyhow::Result<()> {
+/// Will return `Err` if file format not valid or version not compatible
+pub fn read_dictionary_from_path(path: &Path) -> Result<odict::Dictionary> {
+	match path.extension() {
+		Some(ext) if ext == "ifo" => read_stardict_from_path(path),
+		_ => read_odict_from_path(path),
+	}
+}
+
+/// Returns the `ODict` file content of `dictionary`.
+fn odict_bytes(dictionary: &odict::Dictionary) -> Result<Vec<u8>> {
 	let compress_options = odict::CompressOptions::default().quality(8).window_size(22);
 	let writer_options =
 		odict::io::DictionaryWriterOptions::default().compression(compress_options);
 	odict::DictionaryWriter::new()
-		.write_to_path_with_opts(dictionary, path, writer_options)
-		.map_err(|err| anyhow::anyhow!(err))
+		.write_to_bytes_with_opts(dictionary, writer_options)
+		.map_err(Error::odict)
 }
 
-/// # Return
+/// # Errors
 ///
-/// The `ODict` and target path
+/// Will return `Err` if write failed
+pub fn write_odict_to_path(dictionary: &odict::Dictionary, path: &Path) -> Result<()> {
+	std::fs::write(path, odict_bytes(dictionary)?)?;
+
+	Ok(())
+}
+
+/// Write `dictionary` to `path` with [`write_atomically`], so `path` is never left
+/// half-written, e.g. if the app crashes.
 ///
 /// # Errors
 ///
-/// Error message should explain it
-pub async fn import_odict(url: &Url) -> anyhow::Result<(odict::Dictionary, PathBuf)> {
-	let _span = info_span!("import").entered();
-	let t0 = now();
-
-	let path = match url.scheme() {
-		"file" => url
-			.to_file_path()
-			.map_err(|()| anyhow::anyhow!("url not valid: {url}"))?,
-		other => {
-			anyhow::bail!("{url} has unknown schema: {other}");
-		}
-	};
+/// Will return `Err` if write failed
+pub fn write_odict_atomically(dictionary: &odict::Dictionary, path: &Path) -> Result<()> {
+	write_atomically(path, &odict_bytes(dictionary)?)?;
 
-	info!("reading ODict from {}...", path.display());
-	let mut odict = read_odict_from_path(&path)?;
+	Ok(())
+}
 
-	let local_data_dir = AppModel::local_data_dir();
-	if !local_data_dir.exists() {
-		std::fs::create_dir_all(&local_data_dir)?;
+/// Write `dictionary` to `path` with [`write_new_atomically`], which never replaces
+/// an existing file.
+///
+/// # Errors
+///
+/// Will return [`Error::AlreadyExists`] if `path` exists, or another `Err` if write
+/// failed
+pub fn write_new_odict_atomically(dictionary: &odict::Dictionary, path: &Path) -> Result<()> {
+	match write_new_atomically(path, &odict_bytes(dictionary)?) {
+		Ok(()) => Ok(()),
+		Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+			Err(Error::AlreadyExists(path.to_path_buf()))
+		}
+		Err(err) => Err(err.into()),
 	}
+}
 
-	let target_path = if let Some(name) = &odict.name {
-		local_data_dir.join(format!("{}.odict", name.replace(['/', '\\'], "|")))
+/// Returns the file name of a dictionary named `name`.
+pub(crate) fn odict_file_name(name: &str) -> String {
+	format!("{}.odict", name.replace(['/', '\\'], "|"))
+}
+
+/// Set the name of the `ODict` dictionary at `path` to `name` and rename the file
+/// after it, so later imports of the same name conflict with it. Returns the
+/// previous name, which is the file stem if there was none, and the new path.
+///
+/// # Errors
+///
+/// Will return `Err` if the file could not be read or written, or another file is
+/// named after `name`
+pub fn rename_odict(path: &Path, name: &str) -> Result<(String, PathBuf)> {
+	let mut odict = read_odict_from_path(path)?;
+	let stem = path
+		.file_stem()
+		.map(|stem| stem.to_string_lossy().to_string())
+		.unwrap_or_default();
+	let old_name = odict.name.replace(name.to_string()).unwrap_or(stem);
+
+	let new_path = path.with_file_name(odict_file_name(name));
+	if new_path == path {
+		write_odict_atomically(&odict, path)?;
 	} else {
-		let name = path
-			.file_stem()
-			.ok_or(anyhow::anyhow!("path not valid: {}", path.display()))?
-			.to_string_lossy()
-			.to_string();
-		odict.name = Some(name.clone());
-		local_data_dir.join(format!("{name}.odict"))
-	};
-
-	if target_path.exists() {
-		anyhow::bail!("target path exists: {}", target_path.display());
+		write_new_odict_atomically(&odict, &new_path)?;
+		if let Err(err) = fs::remove_file(path) {
+			let _ = fs::remove_file(&new_path);
+			return Err(err.into());
+		}
+	}
+	info!("renamed {old_name:?} in {path:?} to {name:?} in {new_path:?}");
+
+	Ok((old_name, new_path))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::TempDir;
+
+	fn dictionary(name: &str) -> odict::Dictionary {
+		odict::Dictionary {
+			name: Some(name.to_string()),
+			..Default::default()
+		}
 	}
 
-	info!("writing ODict to {target_path:?}...");
-	write_odict_to_path(&odict, &target_path)?;
+	#[test]
+	fn write_atomically() {
+		let dir = TempDir::new("odict-write");
+		let path = dir.path().join("Test.odict");
+
+		write_odict_atomically(&dictionary("old"), &path).unwrap();
+		write_odict_atomically(&dictionary("new"), &path).unwrap();
+		let odict = read_odict_from_path(&path).unwrap();
+		assert_eq!(odict.name.as_deref(), Some("new"));
+		assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+	}
 
-	info!("import used {:.3}s", elapsed_secs(&t0));
+	#[test]
+	fn write_atomically_failed() {
+		let dir = TempDir::new("odict-write-failed");
+		// a directory can't be replaced by a file
+		let path = dir.path().join("Test.odict");
+		std::fs::create_dir(&path).unwrap();
+		std::fs::write(path.join("file"), "").unwrap();
 
-	Ok((odict, target_path))
+		assert!(write_odict_atomically(&dictionary("Test"), &path).is_err());
+		assert!(path.is_dir());
+		assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+	}
+
+	#[test]
+	fn write_new() {
+		let dir = TempDir::new("odict-write-new");
+		let path = dir.path().join("Test.odict");
+
+		write_new_odict_atomically(&dictionary("old"), &path).unwrap();
+		assert!(matches!(
+			write_new_odict_atomically(&dictionary("new"), &path),
+			Err(Error::AlreadyExists(existing)) if existing == path
+		));
+		let odict = read_odict_from_path(&path).unwrap();
+		assert_eq!(odict.name.as_deref(), Some("old"));
+		assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+	}
+
+	#[test]
+	fn rename() {
+		let dir = TempDir::new("odict-rename");
+		let path = dir.path().join("Stem.odict");
+		write_odict_atomically(&odict::Dictionary::default(), &path).unwrap();
+
+		let (old_name, path) = rename_odict(&path, "First").unwrap();
+		assert_eq!(old_name, "Stem");
+		assert_eq!(path, dir.path().join("First.odict"));
+		let (old_name, path) = rename_odict(&path, "Second/Edition").unwrap();
+		assert_eq!(old_name, "First");
+		assert_eq!(path, dir.path().join("Second|Edition.odict"));
+		let odict = read_odict_from_path(&path).unwrap();
+		assert_eq!(odict.name.as_deref(), Some("Second/Edition"));
+		assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+		// keeps the file name if only the name in the file differs
+		let (_, same_path) = rename_odict(&path, "Second/Edition").unwrap();
+		assert_eq!(same_path, path);
+	}
+
+	#[test]
+	fn rename_taken() {
+		let dir = TempDir::new("odict-rename-taken");
+		let path = dir.path().join("First.odict");
+		let other_path = dir.path().join("Second.odict");
+		write_odict_atomically(&dictionary("First"), &path).unwrap();
+		write_odict_atomically(&dictionary("Other"), &other_path).unwrap();
+
+		assert!(matches!(
+			rename_odict(&path, "Second"),
+			Err(Error::AlreadyExists(existing)) if existing == other_path
+		));
+		let odict = read_odict_from_path(&path).unwrap();
+		assert_eq!(odict.name.as_deref(), Some("First"));
+		let odict = read_odict_from_path(&other_path).unwrap();
+		assert_eq!(odict.name.as_deref(), Some("Other"));
+	}
 }
@@ -0,0 +1,355 @@
+//! Wildcard and regular expression search over headwords.
+
+use super::{Matcher, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// How the search term is matched against headwords.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SearchMode {
+	/// Headwords starting with the search term.
+	#[default]
+	Prefix,
+	/// Glob-style wildcards, see [`Glob`].
+	Wildcard,
+	/// Regular expressions matching whole headwords.
+	Regex,
+}
+
+impl SearchMode {
+	pub const ALL: [Self; 3] = [Self::Prefix, Self::Wildcard, Self::Regex];
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+	/// Matched against folded headwords.
+	Glob(String),
+	/// Matched against original headwords, only those starting with `prefix` after
+	/// folding are tried.
+	Regex { regex: Regex, prefix: String },
+}
+
+impl Pattern {
+	/// Returns the pattern of `s` in `mode`, `None` if `mode` is not a pattern mode.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if `s` is not a valid regular expression
+	pub fn new(mode: SearchMode, s: &str) -> Result<Option<Self>> {
+		Ok(match mode {
+			SearchMode::Prefix => None,
+			SearchMode::Wildcard => Some(Self::Glob(s.to_string())),
+			SearchMode::Regex => Some(Self::Regex {
+				regex: Regex::new(&format!("^(?:{s})$"))?,
+				prefix: regex_literal_prefix(s),
+			}),
+		})
+	}
+}
+
+/// Returns the literal text every match of the regular expression `s` starts with.
+///
+/// This is conservative, an empty prefix is returned for anything not simple.
+fn regex_literal_prefix(s: &str) -> String {
+	// alternatives may start with anything
+	if s.contains('|') {
+		return String::new();
+	}
+
+	let mut prefix = String::new();
+	// the pattern is anchored anyway
+	let mut chars = s.strip_prefix('^').unwrap_or(s).chars().peekable();
+	while let Some(mut c) = chars.next() {
+		// escaped punctuation is literal, but `\d`, `\w` etc. are classes
+		if c == '\\' {
+			match chars.next() {
+				Some(escaped) if escaped.is_ascii_punctuation() => c = escaped,
+				_ => break,
+			}
+		} else if !c.is_alphanumeric() && !matches!(c, ' ' | '-' | '\'') {
+			break;
+		}
+		// the last char is optional or repeated
+		if matches!(chars.peek(), Some('?' | '*' | '{')) {
+			break;
+		}
+		prefix.push(c);
+	}
+
+	prefix
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+	Char(char),
+	/// `?`
+	Any,
+	/// `*`
+	Star,
+	/// `[...]`, with ranges of chars and whether it's negated by `!` or `^`.
+	Class(Vec<(char, char)>, bool),
+}
+
+impl Token {
+	fn matches(&self, c: char) -> bool {
+		match self {
+			Self::Char(x) => *x == c,
+			Self::Any | Self::Star => true,
+			Self::Class(ranges, negated) => {
+				ranges
+					.iter()
+					.any(|(start, end)| (*start..=*end).contains(&c))
+					!= *negated
+			}
+		}
+	}
+}
+
+/// A glob pattern matching whole terms: `?` matches a char, `*` matches any number of
+/// chars and `[...]` matches a char in the set, e.g. `[aeiou]`, `[a-z]` or `[!x]`.
+///
+/// An unclosed `[` matches itself.
+#[derive(Debug, Clone)]
+pub struct Glob {
+	tokens: Vec<Token>,
+}
+
+impl Glob {
+	#[must_use]
+	pub fn new(pattern: &str) -> Self {
+		let chars: Vec<char> = pattern.chars().collect();
+		let mut tokens = Vec::new();
+		let mut i = 0;
+		while i < chars.len() {
+			let token = match chars[i] {
+				'?' => Token::Any,
+				'*' => Token::Star,
+				'[' => match Self::parse_class(&chars[i + 1..]) {
+					Some((token, len)) => {
+						i += len;
+						token
+					}
+					None => Token::Char('['),
+				},
+				c => Token::Char(c),
+			};
+			// consecutive stars are the same as one
+			if !(token == Token::Star && tokens.last() == Some(&Token::Star)) {
+				tokens.push(token);
+			}
+			i += 1;
+		}
+
+		Self { tokens }
+	}
+
+	/// Parse a class after `[`, returns the token and the number of chars consumed.
+	fn parse_class(chars: &[char]) -> Option<(Token, usize)> {
+		let negated = matches!(chars.first(), Some('!' | '^'));
+		let mut i = usize::from(negated);
+		let mut ranges = Vec::new();
+		// "]" right after "[" is a member
+		let start = i;
+		while i < chars.len() {
+			let c = chars[i];
+			if c == ']' && i > start {
+				return Some((Token::Class(ranges, negated), i + 1));
+			}
+			if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|end| *end != ']') {
+				ranges.push((c, chars[i + 2]));
+				i += 3;
+			} else {
+				ranges.push((c, c));
+				i += 1;
+			}
+		}
+
+		None
+	}
+
+	/// Add positions reachable by letting stars match nothing.
+	fn closure(&self, positions: &mut Vec<usize>) {
+		let mut i = 0;
+		while i < positions.len() {
+			let p = positions[i];
+			if self.tokens.get(p) == Some(&Token::Star) && !positions.contains(&(p + 1)) {
+				positions.push(p + 1);
+			}
+			i += 1;
+		}
+		positions.sort_unstable();
+	}
+}
+
+/// The state of matching a [`Glob`], which is the set of possible positions in the
+/// pattern and the bytes of an incomplete UTF-8 char.
+#[derive(Debug, Clone)]
+pub struct GlobState {
+	positions: Vec<usize>,
+	partial: Vec<u8>,
+}
+
+impl Matcher for Glob {
+	type State = GlobState;
+
+	fn start(&self) -> Self::State {
+		let mut positions = vec![0];
+		self.closure(&mut positions);
+		GlobState {
+			positions,
+			partial: Vec::new(),
+		}
+	}
+
+	fn accept(&self, state: &Self::State, byte: u8) -> Option<Self::State> {
+		let mut partial = state.partial.clone();
+		partial.push(byte);
+		let c = match std::str::from_utf8(&partial) {
+			Ok(s) => s.chars().next()?,
+			// wait for the rest of the char
+			Err(err) if err.error_len().is_none() => {
+				return Some(GlobState {
+					positions: state.positions.clone(),
+					partial,
+				});
+			}
+			Err(_) => return None,
+		};
+
+		let mut positions = Vec::new();
+		for p in &state.positions {
+			let Some(token) = self.tokens.get(*p) else {
+				continue;
+			};
+			if token.matches(c) {
+				// a star could match more chars
+				let next = if *token == Token::Star { *p } else { p + 1 };
+				if !positions.contains(&next) {
+					positions.push(next);
+				}
+			}
+		}
+		self.closure(&mut positions);
+
+		(!positions.is_empty()).then_some(GlobState {
+			positions,
+			partial: Vec::new(),
+		})
+	}
+
+	fn is_match(&self, state: &Self::State) -> bool {
+		state.partial.is_empty() && state.positions.contains(&self.tokens.len())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Dictionary;
+
+	const TERMS: [&str; 10] = [
+		"a.b", "abc", "abcd", "Abend", "bake", "cake", "café", "color", "colour", "x-ray",
+	];
+
+	fn dictionary() -> Dictionary {
+		let mut odict = odict::Dictionary::default();
+		for term in TERMS {
+			odict.entries.insert(odict::Entry {
+				term: term.to_string(),
+				..Default::default()
+			});
+		}
+
+		Dictionary::new(odict)
+	}
+
+	fn search(mode: SearchMode, s: &str) -> Vec<String> {
+		let pattern = Pattern::new(mode, s).unwrap().unwrap();
+		dictionary().search_pattern(&pattern, 100)
+	}
+
+	fn glob_matches(pattern: &str, term: &str) -> bool {
+		let glob = Glob::new(pattern);
+		term.bytes()
+			.try_fold(glob.start(), |state, byte| glob.accept(&state, byte))
+			.is_some_and(|state| glob.is_match(&state))
+	}
+
+	#[test]
+	fn glob() {
+		assert!(glob_matches("abc", "abc"));
+		assert!(!glob_matches("abc", "abcd"));
+		assert!(glob_matches("a?c", "abc"));
+		assert!(glob_matches("caf?", "café"));
+		assert!(!glob_matches("a?c", "ac"));
+		assert!(glob_matches("a*", "a"));
+		assert!(glob_matches("a**d", "abcd"));
+		assert!(glob_matches("*b*", "abc"));
+		assert!(!glob_matches("*x*", "abc"));
+		assert!(glob_matches("", ""));
+		assert!(!glob_matches("", "a"));
+	}
+
+	#[test]
+	fn glob_classes() {
+		assert!(glob_matches("[abc]", "b"));
+		assert!(!glob_matches("[abc]", "d"));
+		assert!(glob_matches("[a-c]x", "bx"));
+		assert!(!glob_matches("[a-c]x", "dx"));
+		assert!(glob_matches("[!a-c]", "d"));
+		assert!(glob_matches("[^a-c]", "é"));
+		assert!(!glob_matches("[!a-c]", "a"));
+		assert!(glob_matches("[]]", "]"));
+		assert!(glob_matches("[a-]", "-"));
+		// unclosed
+		assert!(glob_matches("a[b", "a[b"));
+		assert!(!glob_matches("a[b", "ab"));
+	}
+
+	#[test]
+	fn search_wildcard() {
+		assert_eq!(search(SearchMode::Wildcard, "?ake"), ["bake", "cake"]);
+		assert_eq!(search(SearchMode::Wildcard, "col*r"), ["color", "colour"]);
+		assert_eq!(search(SearchMode::Wildcard, "[ab]*e*"), ["Abend", "bake"]);
+		assert_eq!(search(SearchMode::Wildcard, "*-*"), ["x-ray"]);
+	}
+
+	#[test]
+	fn search_regex() {
+		assert_eq!(search(SearchMode::Regex, "colou?r"), ["color", "colour"]);
+		assert_eq!(search(SearchMode::Regex, "^abc.*"), ["abc", "abcd"]);
+		assert_eq!(search(SearchMode::Regex, "bake|cake"), ["bake", "cake"]);
+		assert_eq!(search(SearchMode::Regex, r"a\.b"), ["a.b"]);
+		assert_eq!(search(SearchMode::Regex, "caf."), ["café"]);
+		// whole terms only
+		assert!(search(SearchMode::Regex, "ab").is_empty());
+	}
+
+	#[test]
+	fn invalid_regex() {
+		assert!(matches!(
+			Pattern::new(SearchMode::Regex, "a("),
+			Err(crate::Error::InvalidPattern(_))
+		));
+		assert!(Pattern::new(SearchMode::Prefix, "a(").unwrap().is_none());
+	}
+
+	#[test]
+	fn literal_prefix() {
+		assert_eq!(regex_literal_prefix("abc"), "abc");
+		assert_eq!(regex_literal_prefix("^abc.*"), "abc");
+		assert_eq!(regex_literal_prefix("abc+"), "abc");
+		assert_eq!(regex_literal_prefix("colou?r"), "colo");
+		assert_eq!(regex_literal_prefix("ab*"), "a");
+		assert_eq!(regex_literal_prefix("ab{2}"), "a");
+		assert_eq!(regex_literal_prefix("x-ray s"), "x-ray s");
+		assert_eq!(regex_literal_prefix("abc|abd"), "");
+		assert_eq!(regex_literal_prefix("a(b|c)"), "");
+		assert_eq!(regex_literal_prefix(r"a\.b"), "a.b");
+		assert_eq!(regex_literal_prefix(r"a\.?b"), "a");
+		assert_eq!(regex_literal_prefix(r"ab\d"), "ab");
+		assert_eq!(regex_literal_prefix("(?i)abc"), "");
+		assert_eq!(regex_literal_prefix("[ab]c"), "");
+		assert_eq!(regex_literal_prefix(""), "");
+	}
+}
@@ -0,0 +1,434 @@
+//! Reader of `StarDict` dictionaries, see
+//! <https://github.com/huzheng001/stardict-3/blob/master/dict/doc/StarDictFileFormat>.
+
+use super::{Error, Result};
+use flate2::read::GzDecoder;
+use odict::DefinitionType;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Information from the `.ifo` file.
+#[derive(Debug, Clone, Default)]
+pub struct StarDictInfo {
+	pub bookname: String,
+	pub wordcount: usize,
+	pub synwordcount: usize,
+	/// Either 32 or 64.
+	pub idxoffsetbits: u32,
+	pub sametypesequence: Option<String>,
+}
+
+impl StarDictInfo {
+	/// # Errors
+	///
+	/// Will return `Err` if the content is not a valid `.ifo` file
+	pub fn parse(content: &str) -> Result<Self> {
+		let mut lines = content.lines();
+		if lines.next().map(str::trim) != Some("StarDict's dict ifo file") {
+			return Err(Error::format("not a StarDict .ifo file"));
+		}
+
+		let mut info = Self {
+			idxoffsetbits: 32,
+			..Self::default()
+		};
+		for line in lines {
+			let Some((key, value)) = line.split_once('=') else {
+				continue;
+			};
+			let value = value.trim();
+			match key.trim() {
+				"bookname" => info.bookname = value.to_string(),
+				"wordcount" => info.wordcount = value.parse()?,
+				"synwordcount" => info.synwordcount = value.parse()?,
+				"idxoffsetbits" => info.idxoffsetbits = value.parse()?,
+				"sametypesequence" => info.sametypesequence = Some(value.to_string()),
+				_ => (),
+			}
+		}
+
+		if info.bookname.is_empty() {
+			return Err(Error::format("bookname is missing in .ifo file"));
+		}
+		if !matches!(info.idxoffsetbits, 32 | 64) {
+			return Err(Error::Format(format!(
+				"idxoffsetbits not valid: {}",
+				info.idxoffsetbits
+			)));
+		}
+
+		Ok(info)
+	}
+}
+
+/// Read the `StarDict` dictionary described by the `.ifo` file at `path`.
+///
+/// Definitions are converted to plain text, and synonyms in the `.syn` file become
+/// entries which only refer to their headword by "see also", so definitions are not
+/// duplicated. A synonym of more than one headword refers to the first one.
+///
+/// # Errors
+///
+/// Will return `Err` if any of the dictionary files is missing or not valid
+pub fn read_stardict_from_path(path: &Path) -> Result<odict::Dictionary> {
+	let info = StarDictInfo::parse(&std::fs::read_to_string(path)?)?;
+	let base = path.with_extension("");
+
+	let idx = read_first_existing(&base, &[".idx", ".idx.gz"])?
+		.ok_or_else(|| Error::Format(format!(".idx file not found for {}", path.display())))?;
+	let dict = read_first_existing(&base, &[".dict.dz", ".dict"])?
+		.ok_or_else(|| Error::Format(format!(".dict file not found for {}", path.display())))?;
+	let syn = read_first_existing(&base, &[".syn"])?;
+
+	let words = parse_idx(&idx, info.idxoffsetbits)?;
+	let mut definitions: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+	for (word, offset, size) in &words {
+		let data = usize::try_from(*offset)
+			.ok()
+			.and_then(|start| dict.get(start..start.checked_add(*size as usize)?))
+			.ok_or_else(|| Error::Format(format!("data of {word:?} is out of range")))?;
+		definitions
+			.entry(word.as_str())
+			.or_default()
+			.extend(parse_data(data, info.sametypesequence.as_deref())?);
+	}
+
+	let mut synonyms: BTreeMap<String, &str> = BTreeMap::new();
+	if let Some(syn) = &syn {
+		for (synonym, index) in parse_syn(syn)? {
+			let (word, _, _) = words.get(index).ok_or_else(|| {
+				Error::Format(format!("synonym {synonym:?} refers to a missing word"))
+			})?;
+			if !definitions.contains_key(synonym.as_str()) {
+				synonyms.entry(synonym).or_insert(word);
+			}
+		}
+	}
+
+	let mut odict = odict::Dictionary {
+		name: Some(info.bookname),
+		..Default::default()
+	};
+	for (word, texts) in &definitions {
+		odict.entries.insert(new_entry(word, texts));
+	}
+	for (synonym, word) in synonyms {
+		odict.entries.insert(odict::Entry {
+			term: synonym,
+			see_also: Some(odict::EntryRef(word.to_string())),
+			..Default::default()
+		});
+	}
+
+	Ok(odict)
+}
+
+fn new_entry(term: &str, texts: &[String]) -> odict::Entry {
+	let definitions = texts
+		.iter()
+		.flat_map(|text| text.lines())
+		.map(str::trim)
+		.filter(|line| !line.is_empty())
+		.map(|value| {
+			DefinitionType::Definition(odict::Definition {
+				value: value.to_string(),
+				..Default::default()
+			})
+		})
+		.collect();
+	let sense = odict::Sense {
+		definitions,
+		..Default::default()
+	};
+	let etymology = odict::Etymology {
+		senses: std::iter::once(sense).collect(),
+		..Default::default()
+	};
+
+	odict::Entry {
+		term: term.to_string(),
+		etymologies: vec![etymology],
+		..Default::default()
+	}
+}
+
+/// Read the first existing file of `base` with one of `suffixes`, gzip-compressed
+/// files (including dictzip) are decompressed.
+fn read_first_existing(base: &Path, suffixes: &[&str]) -> Result<Option<Vec<u8>>> {
+	for suffix in suffixes {
+		let mut path = OsString::from(base.as_os_str());
+		path.push(suffix);
+		let path = PathBuf::from(path);
+		if !path.is_file() {
+			continue;
+		}
+
+		let content = std::fs::read(&path)?;
+		if suffix.ends_with(".gz") || suffix.ends_with(".dz") {
+			let mut decompressed = Vec::new();
+			GzDecoder::new(content.as_slice()).read_to_end(&mut decompressed)?;
+			return Ok(Some(decompressed));
+		}
+
+		return Ok(Some(content));
+	}
+
+	Ok(None)
+}
+
+/// Split a NUL-terminated string from the start of `bytes`.
+fn take_cstr<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8]> {
+	let end = bytes
+		.iter()
+		.position(|b| *b == 0)
+		.ok_or_else(|| Error::format("string is not terminated"))?;
+	let s = &bytes[..end];
+	*bytes = &bytes[end + 1..];
+	Ok(s)
+}
+
+/// Split a big-endian number of `N` bytes from the start of `bytes`.
+fn take_be<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N]> {
+	let (head, tail) = bytes
+		.split_first_chunk::<N>()
+		.ok_or_else(|| Error::format("unexpected end of data"))?;
+	*bytes = tail;
+	Ok(*head)
+}
+
+/// Returns `(word, offset, size)` of each item in the `.idx` file.
+fn parse_idx(mut idx: &[u8], offset_bits: u32) -> Result<Vec<(String, u64, u32)>> {
+	let mut words = Vec::new();
+	while !idx.is_empty() {
+		let word = String::from_utf8_lossy(take_cstr(&mut idx)?).into_owned();
+		let offset = if offset_bits == 64 {
+			u64::from_be_bytes(take_be(&mut idx)?)
+		} else {
+			u64::from(u32::from_be_bytes(take_be(&mut idx)?))
+		};
+		let size = u32::from_be_bytes(take_be(&mut idx)?);
+		words.push((word, offset, size));
+	}
+
+	Ok(words)
+}
+
+/// Returns `(synonym, index of word)` of each item in the `.syn` file.
+fn parse_syn(mut syn: &[u8]) -> Result<Vec<(String, usize)>> {
+	let mut synonyms = Vec::new();
+	while !syn.is_empty() {
+		let synonym = String::from_utf8_lossy(take_cstr(&mut syn)?).into_owned();
+		let index = u32::from_be_bytes(take_be(&mut syn)?) as usize;
+		synonyms.push((synonym, index));
+	}
+
+	Ok(synonyms)
+}
+
+/// Returns the textual fields of a word's data, binary fields like sounds and
+/// pictures are dropped.
+fn parse_data(mut data: &[u8], same_type_sequence: Option<&str>) -> Result<Vec<String>> {
+	let mut texts = Vec::new();
+
+	let mut push_field = |field_type: u8, field: &[u8]| {
+		let text = String::from_utf8_lossy(field);
+		match field_type {
+			b'm' | b'l' | b'y' | b'k' => texts.push(text.into_owned()),
+			b'g' | b'h' | b'x' => texts.push(strip_markup(&text)),
+			// phonetics, binary and unknown fields
+			_ => (),
+		}
+	};
+
+	if let Some(types) = same_type_sequence {
+		let types = types.as_bytes();
+		for (i, field_type) in types.iter().enumerate() {
+			let is_last = i + 1 == types.len();
+			let field = if is_last {
+				std::mem::take(&mut data)
+			} else if field_type.is_ascii_lowercase() {
+				take_cstr(&mut data)?
+			} else {
+				let size = u32::from_be_bytes(take_be(&mut data)?) as usize;
+				let field = data
+					.get(..size)
+					.ok_or_else(|| Error::format("unexpected end of data"))?;
+				data = &data[size..];
+				field
+			};
+			push_field(*field_type, field);
+		}
+	} else {
+		while let Some((field_type, rest)) = data.split_first() {
+			data = rest;
+			let field = if field_type.is_ascii_lowercase() {
+				take_cstr(&mut data)?
+			} else {
+				let size = u32::from_be_bytes(take_be(&mut data)?) as usize;
+				let field = data
+					.get(..size)
+					.ok_or_else(|| Error::format("unexpected end of data"))?;
+				data = &data[size..];
+				field
+			};
+			push_field(*field_type, field);
+		}
+	}
+
+	Ok(texts)
+}
+
+/// Convert HTML or XDXF markup to plain text.
+fn strip_markup(s: &str) -> String {
+	let mut text = String::with_capacity(s.len());
+	let mut tag = None::<String>;
+	let mut chars = s.chars().peekable();
+	while let Some(c) = chars.next() {
+		match (&mut tag, c) {
+			// a `<` not followed by a tag name is text, as in "a < b"
+			(None, '<')
+				if chars
+					.peek()
+					.is_some_and(|&c| c.is_ascii_alphabetic() || c == '/' || c == '!') =>
+			{
+				tag = Some(String::new());
+			}
+			(Some(name), '>') => {
+				let name = name
+					.trim_start_matches('/')
+					.split(|c: char| c.is_whitespace() || c == '/')
+					.next()
+					.unwrap_or_default()
+					.to_ascii_lowercase();
+				if matches!(name.as_str(), "br" | "p" | "div" | "li" | "tr") {
+					text.push('\n');
+				}
+				tag = None;
+			}
+			(Some(name), c) => name.push(c),
+			(None, c) => text.push(c),
+		}
+	}
+
+	text.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&nbsp;", " ")
+		.replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::TempDir;
+
+	/// Files of a `StarDict` dictionary with "hello" and "world", in a temporary
+	/// directory.
+	struct Fixture {
+		dir: TempDir,
+		idx: Vec<u8>,
+		dict: Vec<u8>,
+	}
+
+	impl Fixture {
+		fn new(name: &str) -> Self {
+			let dir = TempDir::new(&format!("stardict-{name}"));
+			std::fs::write(
+				dir.path().join("test.ifo"),
+				"StarDict's dict ifo file\nversion=3.0.0\nbookname=Test\nwordcount=2\n\
+				 sametypesequence=m\n",
+			)
+			.unwrap();
+
+			let mut idx = Vec::new();
+			let mut dict = Vec::new();
+			for (word, definition) in [("hello", "a greeting"), ("world", "the earth")] {
+				idx.extend_from_slice(word.as_bytes());
+				idx.push(0);
+				idx.extend_from_slice(&u32::try_from(dict.len()).unwrap().to_be_bytes());
+				idx.extend_from_slice(&u32::try_from(definition.len()).unwrap().to_be_bytes());
+				dict.extend_from_slice(definition.as_bytes());
+			}
+
+			let fixture = Self { dir, idx, dict };
+			fixture.write(&fixture.idx, &fixture.dict);
+			fixture
+		}
+
+		fn write(&self, idx: &[u8], dict: &[u8]) {
+			std::fs::write(self.dir.path().join("test.idx"), idx).unwrap();
+			std::fs::write(self.dir.path().join("test.dict"), dict).unwrap();
+		}
+
+		fn read(&self) -> Result<odict::Dictionary> {
+			read_stardict_from_path(&self.dir.path().join("test.ifo"))
+		}
+	}
+
+	#[test]
+	fn read() {
+		let fixture = Fixture::new("read");
+
+		let odict = fixture.read().unwrap();
+		assert_eq!(odict.name.as_deref(), Some("Test"));
+		assert_eq!(odict.entries.len(), 2);
+		assert!(odict.entries.get("hello").is_some());
+	}
+
+	#[test]
+	fn read_truncated_idx() {
+		let fixture = Fixture::new("idx");
+		// each item is the word, a NUL, the offset and the size
+		let items = ["hello".len() + 9, "hello".len() + 9 + "world".len() + 9];
+
+		for len in 0..fixture.idx.len() {
+			fixture.write(&fixture.idx[..len], &fixture.dict);
+			match fixture.read() {
+				Ok(odict) => {
+					assert!(len == 0 || items.contains(&len), "{len}");
+					assert_eq!(odict.entries.len(), usize::from(len > 0), "{len}");
+				}
+				Err(err) => assert!(matches!(err, Error::Format(_)), "{len}: {err:?}"),
+			}
+		}
+	}
+
+	#[test]
+	fn read_truncated_dict() {
+		let fixture = Fixture::new("dict");
+
+		for len in 0..fixture.dict.len() {
+			fixture.write(&fixture.idx, &fixture.dict[..len]);
+			let result = fixture.read();
+			assert!(matches!(result, Err(Error::Format(_))), "{len}: {result:?}");
+		}
+	}
+
+	#[test]
+	fn parse_truncated_data() {
+		// a text field and a sized binary field, without sametypesequence
+		let mut data = b"mtext\0W".to_vec();
+		data.extend_from_slice(&4u32.to_be_bytes());
+		data.extend_from_slice(b"\x01\x02\x03\x04");
+		assert_eq!(parse_data(&data, None).unwrap(), ["text"]);
+
+		for len in 1..data.len() {
+			if len == b"mtext\0".len() {
+				continue;
+			}
+			let result = parse_data(&data[..len], None);
+			assert!(matches!(result, Err(Error::Format(_))), "{len}: {result:?}");
+		}
+	}
+
+	#[test]
+	fn strip() {
+		assert_eq!(strip_markup("<b>bold</b> text"), "bold text");
+		assert_eq!(strip_markup("line<br/>next<!-- note -->"), "line\nnext");
+		assert_eq!(strip_markup("a < b and c <= d"), "a < b and c <= d");
+		assert_eq!(strip_markup("x<1 &lt;tag&gt; y<"), "x<1 <tag> y<");
+	}
+}
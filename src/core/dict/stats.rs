@@ -0,0 +1,233 @@
+//! Metadata and statistics of dictionaries.
+
+use super::{Result, read_odict_file_from_path};
+use odict::DefinitionType;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Compression of `ODict` files.
+///
+/// It's a constant rather than read from files, because `ODict` always compresses them
+/// with Brotli and the format has no field to tell otherwise.
+pub const ODICT_COMPRESSION: &str = "Brotli";
+
+/// How long it takes to make the index of a dictionary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct IndexTiming {
+	/// Seconds spent building the index, or reading it from the cache.
+	pub secs: f32,
+	/// Whether the index is read from the cache.
+	pub cached: bool,
+}
+
+/// Statistics of the content of a dictionary, see [`Dictionary::stats`](super::Dictionary::stats).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DictStats {
+	pub name: Option<String>,
+	/// Entries with content, those which only refer to another entry are counted in
+	/// `references`.
+	pub entries: usize,
+	pub references: usize,
+	pub senses: usize,
+	/// Definitions, including those in groups.
+	pub definitions: usize,
+	pub examples: usize,
+	/// Number of senses of each part of speech.
+	pub parts_of_speech: BTreeMap<String, usize>,
+	/// Languages of translations.
+	pub languages: BTreeSet<String>,
+	pub index: IndexTiming,
+}
+
+impl DictStats {
+	/// Count the content of `odict`.
+	#[must_use]
+	pub fn new(odict: &odict::Dictionary, index: IndexTiming) -> Self {
+		let mut stats = Self {
+			name: odict.name.clone(),
+			index,
+			..Default::default()
+		};
+
+		for entry in &odict.entries {
+			if entry.etymologies.is_empty() && entry.see_also.is_some() {
+				stats.references += 1;
+				continue;
+			}
+			stats.entries += 1;
+			for sense in entry.etymologies.iter().flat_map(|ety| &ety.senses) {
+				stats.senses += 1;
+				*stats
+					.parts_of_speech
+					.entry(sense.pos.to_string())
+					.or_default() += 1;
+				stats.languages.extend(
+					sense
+						.translations
+						.iter()
+						.map(|translation| translation.lang.clone()),
+				);
+
+				for def in &sense.definitions {
+					match def {
+						DefinitionType::Definition(def) => {
+							stats.definitions += 1;
+							stats.examples += def.examples.len();
+						}
+						DefinitionType::Group(group) => {
+							stats.definitions += group.definitions.len();
+							stats.examples += group
+								.definitions
+								.iter()
+								.map(|def| def.examples.len())
+								.sum::<usize>();
+						}
+					}
+				}
+			}
+		}
+
+		stats
+	}
+}
+
+/// Metadata of a dictionary file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DictFileInfo {
+	pub path: PathBuf,
+	/// Version of the `ODict` format.
+	pub version: String,
+	/// Size in bytes.
+	pub size: u64,
+	pub compression: &'static str,
+}
+
+impl DictFileInfo {
+	/// # Errors
+	///
+	/// Will return `Err` if the file could not be read or is not valid
+	pub fn read(path: &Path) -> Result<Self> {
+		let file = read_odict_file_from_path(path)?;
+
+		Ok(Self {
+			path: path.to_path_buf(),
+			version: file.version.to_string(),
+			size: std::fs::metadata(path)?.len(),
+			compression: ODICT_COMPRESSION,
+		})
+	}
+}
+
+/// Everything known about an installed dictionary.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DictInfo {
+	pub file: DictFileInfo,
+	pub stats: DictStats,
+}
+
+/// A field of [`DictInfo`] to show, see [`DictInfo::fields`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoField {
+	Name,
+	Path,
+	FormatVersion,
+	FileSize,
+	Compression,
+	Entries,
+	References,
+	Senses,
+	Definitions,
+	Examples,
+	PartsOfSpeech,
+	Languages,
+	IndexCached,
+	IndexBuilt,
+}
+
+impl InfoField {
+	/// Returns the English label, the GUI localizes them.
+	#[must_use]
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Name => "Name",
+			Self::Path => "Path",
+			Self::FormatVersion => "Format version",
+			Self::FileSize => "File size",
+			Self::Compression => "Compression",
+			Self::Entries => "Entries",
+			Self::References => "References",
+			Self::Senses => "Senses",
+			Self::Definitions => "Definitions",
+			Self::Examples => "Examples",
+			Self::PartsOfSpeech => "Parts of speech",
+			Self::Languages => "Languages",
+			Self::IndexCached => "Index read from cache in",
+			Self::IndexBuilt => "Index built in",
+		}
+	}
+}
+
+impl DictInfo {
+	/// Returns fields to show and their values, in order.
+	#[must_use]
+	pub fn fields(&self) -> Vec<(InfoField, String)> {
+		let Self { file, stats } = self;
+		let parts_of_speech = stats
+			.parts_of_speech
+			.iter()
+			.map(|(pos, count)| format!("{pos} {count}"))
+			.collect::<Vec<_>>()
+			.join(", ");
+		let languages = stats
+			.languages
+			.iter()
+			.map(String::as_str)
+			.collect::<Vec<_>>()
+			.join(", ");
+		let index = if stats.index.cached {
+			InfoField::IndexCached
+		} else {
+			InfoField::IndexBuilt
+		};
+
+		vec![
+			(InfoField::Name, stats.name.clone().unwrap_or_default()),
+			(InfoField::Path, file.path.display().to_string()),
+			(InfoField::FormatVersion, file.version.clone()),
+			(InfoField::FileSize, format_size(file.size)),
+			(InfoField::Compression, file.compression.to_string()),
+			(InfoField::Entries, stats.entries.to_string()),
+			(InfoField::References, stats.references.to_string()),
+			(InfoField::Senses, stats.senses.to_string()),
+			(InfoField::Definitions, stats.definitions.to_string()),
+			(InfoField::Examples, stats.examples.to_string()),
+			(InfoField::PartsOfSpeech, parts_of_speech),
+			(InfoField::Languages, languages),
+			(index, format!("{:.3}s", stats.index.secs)),
+		]
+	}
+}
+
+/// Format `bytes` in binary units, e.g. "1.5 MiB".
+#[must_use]
+// sizes are shown with one decimal
+#[allow(clippy::cast_precision_loss)]
+pub fn format_size(bytes: u64) -> String {
+	const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+	if bytes < 1024 {
+		return format!("{bytes} B");
+	}
+	let mut size = bytes as f64 / 1024.0;
+	let mut unit = UNITS[0];
+	for next in &UNITS[1..] {
+		if size < 1024.0 {
+			break;
+		}
+		size /= 1024.0;
+		unit = next;
+	}
+
+	format!("{size:.1} {unit}")
+}
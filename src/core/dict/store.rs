@@ -0,0 +1,324 @@
+//! Directories where dictionaries and data derived from them are stored.
+
+use super::{IndexCache, LazyDict, Result};
+use directories::ProjectDirs;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+use tracing::{info, warn};
+
+/// Name of the data directories.
+pub const APP_NAME: &str = "mydict";
+
+/// Used if `XDG_DATA_DIRS` is not set or empty, as the XDG Base Directory
+/// Specification says.
+const DEFAULT_DATA_DIRS: &str = "/usr/local/share:/usr/share";
+
+/// Directories of dictionaries, which are given to the app and the command line
+/// interface, so the library can be used with any directories.
+///
+/// Dictionaries are found in all directories recursively. If dictionaries of the same
+/// name, i.e. file stem, are found in more than one place, the first one is used in
+/// this order:
+///
+/// 1. `data_dir`
+/// 2. `extra_dirs`, in the given order
+/// 3. `system_dirs`, in the given order
+///
+/// Inside one directory, the one with the smallest path wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictStore {
+	/// Where dictionaries are imported to, only those in it can be renamed or deleted.
+	pub data_dir: PathBuf,
+	/// Read-only directories chosen by users, e.g. a shared network mount. The GUI
+	/// sets them from its config, [`DictStore::from_env`] leaves them empty.
+	pub extra_dirs: Vec<PathBuf>,
+	/// Read-only directories of dictionaries installed system-wide.
+	pub system_dirs: Vec<PathBuf>,
+	/// Where indexes of dictionaries are cached.
+	pub cache_dir: PathBuf,
+}
+
+impl DictStore {
+	#[must_use]
+	pub fn new(data_dir: PathBuf, cache_dir: PathBuf) -> Self {
+		Self {
+			data_dir,
+			extra_dirs: Vec::new(),
+			system_dirs: Vec::new(),
+			cache_dir,
+		}
+	}
+
+	#[must_use]
+	pub fn with_extra_dirs(mut self, extra_dirs: Vec<PathBuf>) -> Self {
+		self.extra_dirs = extra_dirs;
+		self
+	}
+
+	#[must_use]
+	pub fn with_system_dirs(mut self, system_dirs: Vec<PathBuf>) -> Self {
+		self.system_dirs = system_dirs;
+		self
+	}
+
+	/// Returns the directories of the platform, e.g. `~/.local/share/mydict` on Linux,
+	/// with `mydict` in every directory of `XDG_DATA_DIRS` for system dictionaries.
+	/// There are no extra directories, see [`DictStore::with_extra_dirs`].
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if no valid home directory could be found
+	pub fn from_env() -> Result<Self> {
+		let dirs = ProjectDirs::from("", "", APP_NAME).ok_or_else(|| {
+			io::Error::new(io::ErrorKind::NotFound, "no valid home directory found")
+		})?;
+
+		Ok(Self::new(
+			dirs.data_dir().to_path_buf(),
+			dirs.cache_dir().to_path_buf(),
+		)
+		.with_system_dirs(system_data_dirs(env::var_os("XDG_DATA_DIRS"))))
+	}
+
+	/// Where indexes of dictionaries are cached.
+	#[must_use]
+	pub fn index_cache(&self) -> IndexCache {
+		IndexCache::new(self.cache_dir.join("index"))
+	}
+
+	#[must_use]
+	pub fn word_lists_path(&self) -> PathBuf {
+		self.data_dir.join("word_lists.json")
+	}
+
+	#[must_use]
+	pub fn history_path(&self) -> PathBuf {
+		self.data_dir.join("history.json")
+	}
+
+	/// Whether the dictionary at `path` is imported, so it can be changed.
+	#[must_use]
+	pub fn is_imported(&self, path: &Path) -> bool {
+		path.starts_with(&self.data_dir)
+	}
+
+	/// Returns a sorted list of all dictionaries under the data directories, see
+	/// [`DictStore`] for which one is used if names are duplicated.
+	///
+	/// Directories which could not be read are skipped with a warning, so one bad
+	/// directory doesn't hide the dictionaries in the others.
+	#[must_use]
+	pub fn dict_paths(&self) -> Vec<PathBuf> {
+		let mut paths = Vec::new();
+		let mut names = HashSet::new();
+
+		let dirs = std::iter::once(&self.data_dir)
+			.chain(&self.extra_dirs)
+			.chain(&self.system_dirs);
+		for dir in dirs {
+			if !dir.is_dir() {
+				continue;
+			}
+
+			let mut dir_paths = Vec::new();
+			find_dict_paths(dir, &mut dir_paths);
+			dir_paths.sort();
+			for path in dir_paths {
+				let name = path.file_stem().map(ToOwned::to_owned).unwrap_or_default();
+				if names.insert(name) {
+					paths.push(path);
+				} else {
+					info!(
+						"skip {}, a dictionary of the same name is found before",
+						path.display()
+					);
+				}
+			}
+		}
+
+		paths.sort();
+
+		paths
+	}
+
+	/// Returns dictionaries under the data directories, which are not loaded yet.
+	#[must_use]
+	pub fn dicts(&self) -> Vec<LazyDict> {
+		self.dict_paths()
+			.into_iter()
+			.map(|p| {
+				info!("found ODict {}", p.display());
+				LazyDict::new(p)
+			})
+			.collect()
+	}
+}
+
+/// Returns `mydict` in each of `xdg_data_dirs`, which is the value of
+/// `XDG_DATA_DIRS`. Relative paths are ignored, as the specification says.
+fn system_data_dirs(xdg_data_dirs: Option<OsString>) -> Vec<PathBuf> {
+	let dirs = xdg_data_dirs
+		.filter(|dirs| !dirs.is_empty())
+		.unwrap_or_else(|| DEFAULT_DATA_DIRS.into());
+
+	let mut system_dirs = Vec::new();
+	for dir in env::split_paths(&dirs) {
+		let dir = dir.join(APP_NAME);
+		if dir.is_absolute() && !system_dirs.contains(&dir) {
+			system_dirs.push(dir);
+		}
+	}
+
+	system_dirs
+}
+
+/// Push paths of `ODict` files in `dir` to `paths` recursively, symbolic links to
+/// directories are not followed, so there are no cycles. Directories and entries
+/// which could not be read are skipped with a warning.
+fn find_dict_paths(dir: &Path, paths: &mut Vec<PathBuf>) {
+	let entries = match fs::read_dir(dir) {
+		Ok(entries) => entries,
+		Err(err) => {
+			warn!("failed to read dictionaries in {}: {err}", dir.display());
+			return;
+		}
+	};
+
+	for entry in entries {
+		let entry = match entry {
+			Ok(entry) => entry,
+			Err(err) => {
+				warn!("failed to read an entry of {}: {err}", dir.display());
+				continue;
+			}
+		};
+		let path = entry.path();
+		match entry.file_type() {
+			Ok(file_type) if file_type.is_dir() => find_dict_paths(&path, paths),
+			Ok(_) if path.is_file() && path.extension().is_some_and(|s| s == "odict") => {
+				paths.push(path);
+			}
+			Ok(_) => {}
+			Err(err) => warn!("failed to read {}: {err}", path.display()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::TempDir;
+
+	#[cfg(unix)]
+	#[test]
+	fn dict_paths_skip_unreadable_dirs() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let dir = TempDir::new("store-unreadable");
+		let root = dir.path();
+		let data_dir = root.join("data");
+		let unreadable = data_dir.join("unreadable");
+		fs::create_dir_all(&unreadable).unwrap();
+		fs::write(data_dir.join("English.odict"), "").unwrap();
+		fs::write(unreadable.join("Hidden.odict"), "").unwrap();
+		fs::write(root.join("file"), "").unwrap();
+		fs::set_permissions(&unreadable, fs::Permissions::from_mode(0o000)).unwrap();
+		// root reads it anyway
+		let is_readable = fs::read_dir(&unreadable).is_ok();
+
+		let store = DictStore::new(data_dir.clone(), root.join("cache"))
+			.with_extra_dirs(vec![root.join("missing"), root.join("file")]);
+		let paths = store.dict_paths();
+
+		fs::set_permissions(&unreadable, fs::Permissions::from_mode(0o755)).unwrap();
+		let mut expected = vec![data_dir.join("English.odict")];
+		if is_readable {
+			expected.push(unreadable.join("Hidden.odict"));
+		}
+		assert_eq!(paths, expected);
+	}
+
+	/// Create empty dictionary files at `paths` relative to `root`.
+	fn touch(root: &Path, paths: &[&str]) {
+		for path in paths {
+			let path = root.join(path);
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(path, "").unwrap();
+		}
+	}
+
+	#[test]
+	fn dict_paths_precedence() {
+		let dir = TempDir::new("store-precedence");
+		let root = dir.path();
+		touch(
+			root,
+			&[
+				"data/Both.odict",
+				"data/Data.odict",
+				"extra1/Both.odict",
+				"extra1/Extra.odict",
+				"extra1/nested/Nested.odict",
+				"extra2/Extra.odict",
+				"extra2/System.odict",
+				"extra2/notes.txt",
+				"system1/mydict/Both.odict",
+				"system1/mydict/System.odict",
+				"system1/mydict/Only.odict",
+				"system2/mydict/Only.odict",
+				"system2/mydict/Last.odict",
+			],
+		);
+		let xdg_data_dirs = env::join_paths([root.join("system1"), root.join("system2")]).unwrap();
+
+		let store = DictStore::new(root.join("data"), root.join("cache"))
+			.with_extra_dirs(vec![root.join("extra1"), root.join("extra2")])
+			.with_system_dirs(system_data_dirs(Some(xdg_data_dirs)));
+		let mut expected = [
+			"data/Both.odict",
+			"data/Data.odict",
+			"extra1/Extra.odict",
+			"extra1/nested/Nested.odict",
+			"extra2/System.odict",
+			"system1/mydict/Only.odict",
+			"system2/mydict/Last.odict",
+		]
+		.map(|path| root.join(path));
+		expected.sort();
+		assert_eq!(store.dict_paths(), expected);
+	}
+
+	#[test]
+	fn dict_paths_same_name_in_one_dir() {
+		let dir = TempDir::new("store-same-dir");
+		let root = dir.path();
+		touch(
+			root,
+			&["data/b/Test.odict", "data/a/Test.odict", "data/Test.odict"],
+		);
+
+		let store = DictStore::new(root.join("data"), root.join("cache"));
+		assert_eq!(store.dict_paths(), [root.join("data/Test.odict")]);
+		assert!(store.is_imported(&root.join("data/Test.odict")));
+		assert!(!store.is_imported(&root.join("extra/Test.odict")));
+	}
+
+	#[test]
+	fn xdg_data_dirs() {
+		assert_eq!(
+			system_data_dirs(Some("/opt/share:relative:/opt/share:/usr/share/".into())),
+			[
+				PathBuf::from("/opt/share/mydict"),
+				PathBuf::from("/usr/share/mydict")
+			]
+		);
+		let defaults = [
+			PathBuf::from("/usr/local/share/mydict"),
+			PathBuf::from("/usr/share/mydict"),
+		];
+		assert_eq!(system_data_dirs(None), defaults);
+		assert_eq!(system_data_dirs(Some(OsString::new())), defaults);
+	}
+}
@@ -0,0 +1,205 @@
+//! Lookup history.
+
+use crate::{Result, format_timestamp, read_json, unix_now, write_json};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Maximum number of items kept in history, older items are dropped first.
+pub const HISTORY_LIMIT: usize = 1000;
+
+/// Lookups of related terms within this many seconds are considered as typing one word.
+const TYPING_INTERVAL: u64 = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryItem {
+	pub term: String,
+	/// Name of the dictionary, `None` means all dictionaries.
+	pub dict: Option<String>,
+	/// Seconds since the Unix epoch.
+	pub timestamp: u64,
+}
+
+impl HistoryItem {
+	#[must_use]
+	pub fn new(term: String, dict: Option<String>) -> Self {
+		Self {
+			term,
+			dict,
+			timestamp: unix_now(),
+		}
+	}
+}
+
+/// Load history from the JSON file at `path`, returns an empty history if the file
+/// doesn't exist.
+///
+/// # Errors
+///
+/// Will return `Err` if the file could not be read or parsed, see [`read_json`]
+pub fn load(path: &Path) -> Result<Vec<HistoryItem>> {
+	Ok(read_json(path)?.unwrap_or_default())
+}
+
+/// # Errors
+///
+/// Will return `Err` if the file could not be written
+pub fn save(history: &[HistoryItem], path: &Path) -> Result<()> {
+	write_json(path, &history)
+}
+
+/// Append `item` to `history`.
+///
+/// When typing, a lookup is done on every keystroke, so the last item is replaced
+/// instead if `item` looks like the continuation of it.
+pub fn record(history: &mut Vec<HistoryItem>, item: HistoryItem) {
+	match history.last_mut() {
+		Some(last)
+			if last.dict == item.dict
+				&& item.timestamp.saturating_sub(last.timestamp) < TYPING_INTERVAL
+				&& (item.term.starts_with(&last.term) || last.term.starts_with(&item.term)) =>
+		{
+			*last = item;
+		}
+		_ => history.push(item),
+	}
+
+	if history.len() > HISTORY_LIMIT {
+		history.drain(..history.len() - HISTORY_LIMIT);
+	}
+}
+
+/// Update items of the dictionary named `from` after it's renamed to `to`, returns
+/// whether any item is updated.
+pub fn rename_dict(history: &mut [HistoryItem], from: &str, to: &str) -> bool {
+	let mut renamed = false;
+	for item in history {
+		if item.dict.as_deref() == Some(from) {
+			item.dict = Some(to.to_string());
+			renamed = true;
+		}
+	}
+
+	renamed
+}
+
+/// Export `history` as CSV with a header line.
+#[must_use]
+pub fn to_csv(history: &[HistoryItem]) -> String {
+	let mut csv = String::from("term,dictionary,time\n");
+	for item in history {
+		csv.push_str(&csv_field(&item.term));
+		csv.push(',');
+		csv.push_str(&csv_field(item.dict.as_deref().unwrap_or_default()));
+		csv.push(',');
+		csv.push_str(&format_timestamp(item.timestamp));
+		csv.push('\n');
+	}
+
+	csv
+}
+
+/// Quote `s` if needed, see RFC 4180.
+#[must_use]
+pub fn csv_field(s: &str) -> String {
+	if s.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", s.replace('"', "\"\""))
+	} else {
+		s.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::TempDir;
+
+	fn item(term: &str, dict: Option<&str>, timestamp: u64) -> HistoryItem {
+		HistoryItem {
+			term: term.to_string(),
+			dict: dict.map(ToString::to_string),
+			timestamp,
+		}
+	}
+
+	fn terms(history: &[HistoryItem]) -> Vec<&str> {
+		history.iter().map(|item| item.term.as_str()).collect()
+	}
+
+	#[test]
+	fn record_typing() {
+		let mut history = Vec::new();
+
+		for (timestamp, term) in (100..).zip(["r", "ru", "run", "ru", "rune"]) {
+			record(&mut history, item(term, None, timestamp));
+		}
+		assert_eq!(terms(&history), ["rune"]);
+
+		// a later lookup, another word or another dictionary is a new item
+		record(&mut history, item("runes", None, 200));
+		record(&mut history, item("walk", None, 200));
+		record(&mut history, item("walker", Some("English"), 200));
+		assert_eq!(terms(&history), ["rune", "runes", "walk", "walker"]);
+	}
+
+	#[test]
+	fn record_limit() {
+		let mut history = Vec::new();
+
+		for i in 0..HISTORY_LIMIT + 10 {
+			record(&mut history, item(&format!("word{i}"), None, 0));
+		}
+		assert_eq!(history.len(), HISTORY_LIMIT);
+		assert_eq!(history[0].term, "word10");
+		assert_eq!(
+			history.last().map(|item| item.term.clone()),
+			Some(format!("word{}", HISTORY_LIMIT + 9))
+		);
+	}
+
+	#[test]
+	fn rename_items() {
+		let mut history = vec![item("run", Some("English"), 0), item("walk", None, 0)];
+
+		assert!(rename_dict(&mut history, "English", "Eng"));
+		assert_eq!(history[0].dict.as_deref(), Some("Eng"));
+		assert_eq!(history[1].dict, None);
+		assert!(!rename_dict(&mut history, "English", "Eng"));
+	}
+
+	#[test]
+	fn csv_escaped() {
+		let history = vec![
+			item("run", None, 0),
+			item("a, b", Some("Say \"hi\""), 86400),
+			item("line\nbreak", Some("English"), 90061),
+		];
+
+		assert_eq!(
+			to_csv(&history),
+			"term,dictionary,time\n\
+			 run,,1970-01-01T00:00:00Z\n\
+			 \"a, b\",\"Say \"\"hi\"\"\",1970-01-02T00:00:00Z\n\
+			 \"line\nbreak\",English,1970-01-02T01:01:01Z\n"
+		);
+	}
+
+	#[test]
+	fn load_saved() {
+		let dir = TempDir::new("history");
+		let path = dir.path().join("data").join("history.json");
+		assert!(load(&path).unwrap().is_empty());
+
+		let history = vec![item("run", Some("English"), 1), item("walk", None, 2)];
+		save(&history, &path).unwrap();
+		assert_eq!(load(&path).unwrap(), history);
+		assert!(!dir.path().join("data").join("history.json.tmp").exists());
+
+		std::fs::write(&path, "not json").unwrap();
+		assert!(matches!(load(&path), Err(crate::Error::Unreadable { .. })));
+		// the unreadable file is kept, so saving doesn't overwrite it
+		save(&history, &path).unwrap();
+		let backup = dir.path().join("data").join("history.json.bak");
+		assert_eq!(std::fs::read_to_string(backup).unwrap(), "not json");
+		assert_eq!(load(&path).unwrap(), history);
+	}
+}
@@ -0,0 +1,400 @@
+//! Machine-readable JSON form of lookups, completions and dictionary information.
+//!
+//! Every document has a `schema_version`, which is bumped whenever a field is
+//! removed or changes meaning. Adding fields doesn't bump it, so consumers should
+//! ignore unknown fields.
+//!
+//! Lookup:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "query": "run",
+//!   "results": [
+//!     {
+//!       "dictionary": "English",
+//!       "entry": {
+//!         "term": "run",
+//!         "see_also": null,
+//!         "etymologies": [
+//!           {
+//!             "description": null,
+//!             "senses": [
+//!               {
+//!                 "pos": "verb",
+//!                 "definitions": [
+//!                   {
+//!                     "type": "definition",
+//!                     "value": "To move swiftly.",
+//!                     "examples": ["He runs every day."],
+//!                     "notes": []
+//!                   },
+//!                   {
+//!                     "type": "group",
+//!                     "description": "To manage.",
+//!                     "definitions": [
+//!                       { "value": "To operate.", "examples": [], "notes": ["Informal."] }
+//!                     ]
+//!                   }
+//!                 ]
+//!               }
+//!             ]
+//!           }
+//!         ]
+//!       }
+//!     },
+//!     {
+//!       "dictionary": "English",
+//!       "entry": { "term": "ran", "see_also": "run", "etymologies": [] }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Completion:
+//!
+//! ```json
+//! { "schema_version": 1, "prefix": "ru", "terms": ["run", "rune"] }
+//! ```
+//!
+//! Dictionary information:
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "dictionaries": [
+//!     {
+//!       "file": {
+//!         "path": "/home/user/.local/share/mydict/English.odict",
+//!         "version": "2.9.0",
+//!         "size": 1048576,
+//!         "compression": "Brotli"
+//!       },
+//!       "stats": {
+//!         "name": "English",
+//!         "entries": 1000,
+//!         "references": 20,
+//!         "senses": 1200,
+//!         "definitions": 1500,
+//!         "examples": 300,
+//!         "parts_of_speech": { "noun": 700, "verb": 500 },
+//!         "languages": ["fr"],
+//!         "index": { "secs": 0.012, "cached": true }
+//!       }
+//!     }
+//!   ]
+//! }
+//! ```
+
+use crate::DictInfo;
+use odict::{DefinitionType, Entry};
+use serde::Serialize;
+
+/// Version of the JSON schema described in the module documentation.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct Lookup<'a> {
+	pub schema_version: u32,
+	pub query: &'a str,
+	pub results: Vec<LookupResult<'a>>,
+}
+
+impl<'a> Lookup<'a> {
+	#[must_use]
+	pub fn new(query: &'a str) -> Self {
+		Self {
+			schema_version: SCHEMA_VERSION,
+			query,
+			results: Vec::new(),
+		}
+	}
+
+	pub fn push(&mut self, dictionary: String, entry: &'a Entry) {
+		self.results.push(LookupResult {
+			dictionary,
+			entry: entry.into(),
+		});
+	}
+}
+
+#[derive(Debug, Serialize)]
+pub struct LookupResult<'a> {
+	/// Name of the dictionary which the entry belongs to.
+	pub dictionary: String,
+	pub entry: JsonEntry<'a>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Completion<'a> {
+	pub schema_version: u32,
+	pub prefix: &'a str,
+	pub terms: &'a [String],
+}
+
+impl<'a> Completion<'a> {
+	#[must_use]
+	pub fn new(prefix: &'a str, terms: &'a [String]) -> Self {
+		Self {
+			schema_version: SCHEMA_VERSION,
+			prefix,
+			terms,
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+pub struct Info<'a> {
+	pub schema_version: u32,
+	pub dictionaries: &'a [DictInfo],
+}
+
+impl<'a> Info<'a> {
+	#[must_use]
+	pub fn new(dictionaries: &'a [DictInfo]) -> Self {
+		Self {
+			schema_version: SCHEMA_VERSION,
+			dictionaries,
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonEntry<'a> {
+	pub term: &'a str,
+	/// The term which this entry refers to, e.g. the infinitive of a past tense.
+	pub see_also: Option<&'a str>,
+	pub etymologies: Vec<JsonEtymology<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonEtymology<'a> {
+	pub description: Option<&'a str>,
+	pub senses: Vec<JsonSense<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonSense<'a> {
+	/// Part of speech.
+	pub pos: String,
+	pub definitions: Vec<JsonDefinitionType<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonDefinitionType<'a> {
+	Definition(JsonDefinition<'a>),
+	Group {
+		description: &'a str,
+		definitions: Vec<JsonDefinition<'a>>,
+	},
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonDefinition<'a> {
+	pub value: &'a str,
+	pub examples: Vec<&'a str>,
+	pub notes: Vec<&'a str>,
+}
+
+impl<'a> From<&'a Entry> for JsonEntry<'a> {
+	fn from(entry: &'a Entry) -> Self {
+		Self {
+			term: &entry.term,
+			see_also: entry.see_also.as_ref().map(|see_also| see_also.0.as_str()),
+			etymologies: entry
+				.etymologies
+				.iter()
+				.map(|ety| JsonEtymology {
+					description: ety.description.as_deref(),
+					senses: ety
+						.senses
+						.iter()
+						.map(|sense| JsonSense {
+							pos: sense.pos.to_string(),
+							definitions: sense.definitions.iter().map(Into::into).collect(),
+						})
+						.collect(),
+				})
+				.collect(),
+		}
+	}
+}
+
+impl<'a> From<&'a DefinitionType> for JsonDefinitionType<'a> {
+	fn from(def: &'a DefinitionType) -> Self {
+		match def {
+			DefinitionType::Definition(def) => Self::Definition(def.into()),
+			DefinitionType::Group(group) => Self::Group {
+				description: &group.description,
+				definitions: group.definitions.iter().map(Into::into).collect(),
+			},
+		}
+	}
+}
+
+impl<'a> From<&'a odict::Definition> for JsonDefinition<'a> {
+	fn from(def: &'a odict::Definition) -> Self {
+		Self {
+			value: &def.value,
+			examples: def.examples.iter().map(|e| e.value.as_str()).collect(),
+			notes: def.notes.iter().map(|n| n.value.as_str()).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{DictFileInfo, DictStats, IndexTiming};
+	use serde_json::json;
+	use std::path::PathBuf;
+
+	#[test]
+	fn lookup_schema() {
+		let sense = odict::Sense {
+			definitions: vec![
+				DefinitionType::Definition(odict::Definition {
+					value: "To move swiftly.".to_string(),
+					..Default::default()
+				}),
+				DefinitionType::Group(odict::Group {
+					description: "To manage.".to_string(),
+					definitions: vec![odict::Definition {
+						value: "To operate.".to_string(),
+						..Default::default()
+					}],
+					..Default::default()
+				}),
+			],
+			..Default::default()
+		};
+		let pos = sense.pos.to_string();
+		let run = Entry {
+			term: "run".to_string(),
+			etymologies: vec![odict::Etymology {
+				senses: std::iter::once(sense).collect(),
+				..Default::default()
+			}],
+			..Default::default()
+		};
+		let ran = Entry {
+			term: "ran".to_string(),
+			see_also: Some(odict::EntryRef("run".to_string())),
+			..Default::default()
+		};
+
+		let mut lookup = Lookup::new("run");
+		lookup.push("English".to_string(), &run);
+		lookup.push("English".to_string(), &ran);
+		assert_eq!(
+			serde_json::to_value(&lookup).unwrap(),
+			json!({
+				"schema_version": 1,
+				"query": "run",
+				"results": [
+					{
+						"dictionary": "English",
+						"entry": {
+							"term": "run",
+							"see_also": null,
+							"etymologies": [
+								{
+									"description": null,
+									"senses": [
+										{
+											"pos": pos,
+											"definitions": [
+												{
+													"type": "definition",
+													"value": "To move swiftly.",
+													"examples": [],
+													"notes": []
+												},
+												{
+													"type": "group",
+													"description": "To manage.",
+													"definitions": [
+														{ "value": "To operate.", "examples": [], "notes": [] }
+													]
+												}
+											]
+										}
+									]
+								}
+							]
+						}
+					},
+					{
+						"dictionary": "English",
+						"entry": { "term": "ran", "see_also": "run", "etymologies": [] }
+					}
+				]
+			})
+		);
+	}
+
+	#[test]
+	fn completion_schema() {
+		let terms = ["run".to_string(), "rune".to_string()];
+
+		assert_eq!(
+			serde_json::to_value(Completion::new("ru", &terms)).unwrap(),
+			json!({ "schema_version": 1, "prefix": "ru", "terms": ["run", "rune"] })
+		);
+	}
+
+	#[test]
+	fn info_schema() {
+		let info = DictInfo {
+			file: DictFileInfo {
+				path: PathBuf::from("/dicts/English.odict"),
+				version: "2.9.0".to_string(),
+				size: 1_048_576,
+				compression: "Brotli",
+			},
+			stats: DictStats {
+				name: Some("English".to_string()),
+				entries: 1000,
+				references: 20,
+				senses: 1200,
+				definitions: 1500,
+				examples: 300,
+				parts_of_speech: [("noun".to_string(), 700), ("verb".to_string(), 500)].into(),
+				languages: ["fr".to_string()].into(),
+				index: IndexTiming {
+					secs: 0.5,
+					cached: true,
+				},
+			},
+		};
+
+		assert_eq!(
+			serde_json::to_value(Info::new(&[info])).unwrap(),
+			json!({
+				"schema_version": 1,
+				"dictionaries": [
+					{
+						"file": {
+							"path": "/dicts/English.odict",
+							"version": "2.9.0",
+							"size": 1_048_576,
+							"compression": "Brotli"
+						},
+						"stats": {
+							"name": "English",
+							"entries": 1000,
+							"references": 20,
+							"senses": 1200,
+							"definitions": 1500,
+							"examples": 300,
+							"parts_of_speech": { "noun": 700, "verb": 500 },
+							"languages": ["fr"],
+							"index": { "secs": 0.5, "cached": true }
+						}
+					}
+				]
+			})
+		);
+	}
+}
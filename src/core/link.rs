@@ -0,0 +1,128 @@
+//! Internal links between entries, e.g. `mydict://lookup/run?dict=English`.
+
+use percent_encoding::{NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+use url::Url;
+
+pub const LINK_SCHEME: &str = "mydict";
+
+/// A link to look up `term`, optionally in the dictionary named `dict`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LookupLink {
+	pub term: String,
+	pub dict: Option<String>,
+}
+
+impl LookupLink {
+	#[must_use]
+	pub fn new(term: String, dict: Option<String>) -> Self {
+		Self { term, dict }
+	}
+
+	/// Parse an internal link, returns `None` if `url` is not one.
+	///
+	/// Bare words like `run` are also internal links, so that dictionaries can refer
+	/// to other entries with plain Markdown links. Other relative URLs, e.g. `#anchor`
+	/// or `./file.html`, are not.
+	#[must_use]
+	pub fn parse(url: &str) -> Option<Self> {
+		let url = match Url::parse(url) {
+			Ok(url) => url,
+			Err(url::ParseError::RelativeUrlWithoutBase) => {
+				if url.contains(['/', '#', '?']) {
+					return None;
+				}
+				let term = percent_decode_str(url).decode_utf8().ok()?;
+				return (!term.is_empty()).then(|| Self::new(term.into_owned(), None));
+			}
+			Err(_) => return None,
+		};
+
+		if url.scheme() != LINK_SCHEME || url.host_str() != Some("lookup") {
+			return None;
+		}
+
+		let term = percent_decode_str(url.path().trim_start_matches('/'))
+			.decode_utf8()
+			.ok()?
+			.into_owned();
+		if term.is_empty() {
+			return None;
+		}
+		let dict = url
+			.query_pairs()
+			.find(|(key, _)| key == "dict")
+			.map(|(_, value)| value.into_owned());
+
+		Some(Self::new(term, dict))
+	}
+}
+
+impl std::fmt::Display for LookupLink {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{LINK_SCHEME}://lookup/{}",
+			utf8_percent_encode(&self.term, NON_ALPHANUMERIC)
+		)?;
+		if let Some(dict) = &self.dict {
+			write!(f, "?dict={}", utf8_percent_encode(dict, NON_ALPHANUMERIC))?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn link(term: &str, dict: Option<&str>) -> LookupLink {
+		LookupLink::new(term.to_string(), dict.map(str::to_string))
+	}
+
+	#[test]
+	fn parse_bare_words() {
+		assert_eq!(LookupLink::parse("run"), Some(link("run", None)));
+		assert_eq!(LookupLink::parse("caf%C3%A9"), Some(link("café", None)));
+		assert_eq!(LookupLink::parse("e.g."), Some(link("e.g.", None)));
+		assert_eq!(LookupLink::parse("and%2For"), Some(link("and/or", None)));
+	}
+
+	#[test]
+	fn parse_lookup_scheme() {
+		assert_eq!(
+			LookupLink::parse("mydict://lookup/run?dict=English"),
+			Some(link("run", Some("English")))
+		);
+		assert_eq!(
+			LookupLink::parse("mydict://lookup/caf%C3%A9"),
+			Some(link("café", None))
+		);
+		assert_eq!(LookupLink::parse("mydict://lookup/"), None);
+		assert_eq!(LookupLink::parse("mydict://other/run"), None);
+	}
+
+	#[test]
+	fn parse_other_urls() {
+		for url in [
+			"#anchor",
+			"run#meaning",
+			"./file.html",
+			"../file.html",
+			"dir/file.html",
+			"/absolute",
+			"?query",
+			"https://example.com/run",
+			"mailto:someone@example.com",
+		] {
+			assert_eq!(LookupLink::parse(url), None, "{url}");
+		}
+	}
+
+	#[test]
+	fn display_parsed() {
+		for link in [link("run", None), link("a b/c", Some("Old English"))] {
+			assert_eq!(LookupLink::parse(&link.to_string()), Some(link));
+		}
+	}
+}
@@ -0,0 +1,253 @@
+//! Renders dictionary entries for display.
+
+use crate::link::LookupLink;
+use odict::{DefinitionType, Entry};
+
+/// Output format of rendered entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+	#[default]
+	Text,
+	Markdown,
+}
+
+impl Format {
+	fn heading(self, level: usize, s: &str) -> String {
+		match self {
+			Self::Text => s.to_string(),
+			Self::Markdown => format!("{} {s}", "#".repeat(level)),
+		}
+	}
+
+	fn strong(self, s: &str) -> String {
+		match self {
+			Self::Text => s.to_string(),
+			Self::Markdown => format!("**{s}**"),
+		}
+	}
+
+	/// Link `label` to the entry of `term` in the dictionary named `dict`.
+	fn lookup_link(self, label: &str, term: &str, dict: Option<&str>) -> String {
+		match self {
+			Self::Text => label.to_string(),
+			Self::Markdown => format!(
+				"[{}]({})",
+				label.replace('[', "\\[").replace(']', "\\]"),
+				LookupLink::new(term.to_string(), dict.map(str::to_string))
+			),
+		}
+	}
+
+	/// Render text of a definition, note or example in the dictionary named `dict`.
+	///
+	/// It may refer to other entries by Markdown links like `[ran](run)`, which become
+	/// internal links to the same dictionary unless the link names another one. Other
+	/// links are left as they are.
+	fn text(self, s: &str, dict: Option<&str>) -> String {
+		let mut text = String::new();
+		let mut rest = s;
+		while let Some(start) = rest.find('[') {
+			text.push_str(&rest[..start]);
+			rest = &rest[start..];
+
+			let Some((label, target, len)) = split_link(rest) else {
+				text.push('[');
+				rest = &rest[1..];
+				continue;
+			};
+			match LookupLink::parse(target) {
+				Some(link) => {
+					let dict = link.dict.as_deref().or(dict);
+					text.push_str(&self.lookup_link(label, &link.term, dict));
+				}
+				None => text.push_str(&rest[..len]),
+			}
+			rest = &rest[len..];
+		}
+		text.push_str(rest);
+
+		text
+	}
+
+	fn separator(self) -> &'static str {
+		match self {
+			Self::Text => "\n",
+			// make every line a paragraph
+			Self::Markdown => "\n\n",
+		}
+	}
+}
+
+/// Split a Markdown link like `[label](target)` from the start of `s`, returns its
+/// label, target and length.
+fn split_link(s: &str) -> Option<(&str, &str, usize)> {
+	let (label, rest) = s.strip_prefix('[')?.split_once("](")?;
+	let (target, _) = rest.split_once(')')?;
+	if label.contains(['[', ']']) || target.is_empty() || target.contains(char::is_whitespace) {
+		return None;
+	}
+
+	Some((label, target, label.len() + target.len() + 4))
+}
+
+/// Returns the letters numbering the `i`th item from zero, i.e. a, b, ..., z, aa, ab, ...
+fn alphabetic_numbering(mut i: usize) -> String {
+	const LETTERS: &[u8; 26] = b"abcdefghijklmnopqrstuvwxyz";
+
+	let mut letters = Vec::new();
+	loop {
+		letters.push(char::from(LETTERS[i % 26]));
+		if i < 26 {
+			break;
+		}
+		i = i / 26 - 1;
+	}
+
+	letters.iter().rev().collect()
+}
+
+/// Render `entry` of the dictionary named `dict` to a string in `format`.
+///
+/// The see-also entry and references in definitions, notes and examples are rendered as
+/// internal links in Markdown, see [`LookupLink`].
+#[must_use]
+pub fn render_entry(entry: &Entry, format: Format, dict: Option<&str>) -> String {
+	let mut lines = Vec::new();
+	lines.push(format.heading(1, &entry.term));
+
+	if let Some(see_also) = &entry.see_also {
+		lines.push(format!(
+			"See also: {}",
+			format.lookup_link(&see_also.0, &see_also.0, dict)
+		));
+	}
+
+	for (i, ety) in entry.etymologies.iter().enumerate() {
+		if entry.etymologies.len() > 1 {
+			lines.push(format.heading(2, &format!("Etymology #{}", i + 1)));
+		}
+		if let Some(desc) = &ety.description {
+			for p in desc.lines() {
+				lines.push(p.to_string());
+			}
+		}
+		for sense in &ety.senses {
+			lines.push(format.strong(&sense.pos.to_string()));
+			for (j, def) in sense.definitions.iter().enumerate() {
+				match def {
+					DefinitionType::Definition(def) => {
+						lines.push(format!("{}. {}", j + 1, format.text(&def.value, dict)));
+						for example in &def.examples {
+							lines.push(format!("\t- {}", format.text(&example.value, dict)));
+						}
+
+						if !def.notes.is_empty() {
+							lines.push("\tNotes".to_string());
+						}
+
+						for (k, note) in def.notes.iter().enumerate() {
+							lines.push(format!(
+								"\t{}. {}",
+								alphabetic_numbering(k),
+								format.text(&note.value, dict)
+							));
+						}
+					}
+					DefinitionType::Group(group) => {
+						lines.push(format!(
+							"{}. {}",
+							j + 1,
+							format.text(&group.description, dict)
+						));
+
+						for (k, def) in group.definitions.iter().enumerate() {
+							lines.push(format!(
+								"\t{}. {}",
+								alphabetic_numbering(k),
+								format.text(&def.value, dict)
+							));
+							for example in &def.examples {
+								lines.push(format!("\t\t- {}", format.text(&example.value, dict)));
+							}
+
+							if !def.notes.is_empty() {
+								lines.push("\t\tNotes".to_string());
+							}
+
+							for (l, note) in def.notes.iter().enumerate() {
+								lines.push(format!(
+									"\t\t{}. {}",
+									l + 1,
+									format.text(&note.value, dict)
+								));
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
+	lines.join(format.separator())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn alphabetic_numbering_letters() {
+		assert_eq!(alphabetic_numbering(0), "a");
+		assert_eq!(alphabetic_numbering(25), "z");
+		assert_eq!(alphabetic_numbering(26), "aa");
+		assert_eq!(alphabetic_numbering(27), "ab");
+		assert_eq!(alphabetic_numbering(26 + 26 * 26 - 1), "zz");
+		assert_eq!(alphabetic_numbering(26 + 26 * 26), "aaa");
+	}
+
+	#[test]
+	fn alphabetic_numbering_large() {
+		for i in 300..1000 {
+			let letters = alphabetic_numbering(i);
+			assert!(letters.bytes().all(|b| b.is_ascii_lowercase()), "{i}");
+		}
+		assert!(!alphabetic_numbering(usize::MAX).is_empty());
+	}
+
+	#[test]
+	fn text_links_references() {
+		let text = "past tense of [run](run), see [the site](https://example.com)";
+
+		assert_eq!(
+			Format::Markdown.text(text, Some("English")),
+			"past tense of [run](mydict://lookup/run?dict=English), \
+			 see [the site](https://example.com)"
+		);
+		assert_eq!(
+			Format::Text.text(text, Some("English")),
+			"past tense of run, see [the site](https://example.com)"
+		);
+		assert_eq!(
+			Format::Markdown.text("[ran](mydict://lookup/run?dict=Verbs)", Some("English")),
+			"[ran](mydict://lookup/run?dict=Verbs)"
+		);
+	}
+
+	#[test]
+	fn text_keeps_relative_urls() {
+		for text in [
+			"see [below](#usage)",
+			"see [the file](./file.html)",
+			"see [the file](../dir/file.html)",
+		] {
+			assert_eq!(Format::Markdown.text(text, Some("English")), text);
+		}
+	}
+
+	#[test]
+	fn text_without_links() {
+		for text in ["a [b] c", "[a](b c)", "[a]()", "[", "a ] [b](", "[[a]](b)"] {
+			assert_eq!(Format::Markdown.text(text, None), text);
+		}
+	}
+}
@@ -1,4 +1,10 @@
-use std::time;
+use crate::{Error, Result};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{fs, io, time};
+use tracing::warn;
 
 #[must_use]
 pub fn now() -> time::Instant {
@@ -9,3 +15,271 @@ pub fn now() -> time::Instant {
 pub fn elapsed_secs(t0: &time::Instant) -> f32 {
 	t0.elapsed().as_secs_f32()
 }
+
+/// Seconds since the Unix epoch.
+#[must_use]
+pub fn unix_now() -> u64 {
+	time::SystemTime::now()
+		.duration_since(time::UNIX_EPOCH)
+		.map_or(0, |d| d.as_secs())
+}
+
+/// Format seconds since the Unix epoch as an ISO 8601 UTC date time, e.g.
+/// "2025-01-31T08:00:00Z".
+#[must_use]
+pub fn format_timestamp(timestamp: u64) -> String {
+	let days = timestamp / 86400;
+	let secs = timestamp % 86400;
+
+	// civil from days, see https://howardhinnant.github.io/date_algorithms.html
+	let z = days + 719_468;
+	let era = z / 146_097;
+	let doe = z % 146_097;
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + u64::from(month <= 2);
+
+	format!(
+		"{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+		secs / 3600,
+		secs % 3600 / 60,
+		secs % 60
+	)
+}
+
+/// Returns a suffix for file names, which is unique among processes and calls.
+pub(crate) fn unique_suffix() -> String {
+	static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+	let nanos = time::SystemTime::now()
+		.duration_since(time::UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_nanos();
+	format!(
+		"{}-{nanos}-{}",
+		std::process::id(),
+		COUNT.fetch_add(1, Ordering::Relaxed)
+	)
+}
+
+/// Write `bytes` to a temporary file next to `path`, then move it to `path` with
+/// `persist`. The temporary file is unique, so concurrent writes don't mix, and it's
+/// removed if anything fails. Parent directories are created if needed.
+fn write_via_temp(
+	path: &Path,
+	bytes: &[u8],
+	persist: impl FnOnce(&Path, &Path) -> io::Result<()>,
+) -> io::Result<()> {
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir)?;
+	}
+
+	let mut tmp_path = path.as_os_str().to_owned();
+	tmp_path.push(format!(".{}.tmp", unique_suffix()));
+	let tmp_path = PathBuf::from(tmp_path);
+	let result = fs::write(&tmp_path, bytes).and_then(|()| persist(&tmp_path, path));
+	// it's renamed if persisted, but not linked
+	if tmp_path.exists() {
+		_ = fs::remove_file(&tmp_path);
+	}
+
+	result
+}
+
+/// Write `bytes` to a temporary file next to `path`, then rename it to `path`, so a
+/// crash never leaves a truncated file.
+///
+/// # Errors
+///
+/// Will return `Err` if the file could not be written
+pub fn write_atomically(path: &Path, bytes: &[u8]) -> io::Result<()> {
+	write_via_temp(path, bytes, |tmp_path, path| fs::rename(tmp_path, path))
+}
+
+/// Like [`write_atomically`], but `path` is never replaced, even if it's created by
+/// another process meanwhile.
+///
+/// # Errors
+///
+/// Will return `Err` of [`io::ErrorKind::AlreadyExists`] if `path` exists, or another
+/// one if the file could not be written
+pub fn write_new_atomically(path: &Path, bytes: &[u8]) -> io::Result<()> {
+	// unlike renaming, linking fails if the target exists
+	write_via_temp(path, bytes, |tmp_path, path| fs::hard_link(tmp_path, path))
+}
+
+/// Read the JSON file at `path`, returns `None` if it doesn't exist.
+///
+/// # Errors
+///
+/// Will return `Err` if the file could not be read or parsed. A file which could not
+/// be parsed is renamed to a `.bak` file next to it first, see [`Error::Unreadable`].
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+	let bytes = match fs::read(path) {
+		Ok(bytes) => bytes,
+		Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(err) => return Err(err.into()),
+	};
+
+	match serde_json::from_slice(&bytes) {
+		Ok(value) => Ok(Some(value)),
+		Err(err) => {
+			let backup = backup_path(path);
+			fs::rename(path, &backup)?;
+			Err(Error::Unreadable {
+				backup,
+				reason: err.to_string(),
+			})
+		}
+	}
+}
+
+/// Write `value` to `path` as pretty-printed JSON, see [`write_atomically`].
+///
+/// # Errors
+///
+/// Will return `Err` if `value` could not be serialized or the file could not be
+/// written
+pub fn write_json(path: &Path, value: &impl Serialize) -> Result<()> {
+	write_atomically(path, serde_json::to_string_pretty(value)?.as_bytes())?;
+
+	Ok(())
+}
+
+/// Returns a path which doesn't exist, by appending `.bak` to `path`, with a number
+/// before it if needed.
+fn backup_path(path: &Path) -> PathBuf {
+	let mut n = 0;
+	loop {
+		let mut backup = path.as_os_str().to_owned();
+		if n > 0 {
+			backup.push(format!(".{n}"));
+		}
+		backup.push(".bak");
+		let backup = PathBuf::from(backup);
+		if !backup.exists() {
+			return backup;
+		}
+		n += 1;
+	}
+}
+
+/// A temporary directory, which is removed when dropped.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+	/// Create an empty directory named after `name`, which is unique among processes.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if the directory could not be created
+	pub(crate) fn create(name: &str) -> io::Result<Self> {
+		let path = std::env::temp_dir().join(format!("mydict-{name}-{}", unique_suffix()));
+		fs::create_dir_all(&path)?;
+
+		Ok(Self(path))
+	}
+
+	/// Create a directory for the test `name`, see [`Self::create`].
+	#[cfg(test)]
+	pub(crate) fn new(name: &str) -> Self {
+		Self::create(&format!("test-{name}")).unwrap()
+	}
+
+	pub(crate) fn path(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		if let Err(err) = fs::remove_dir_all(&self.0) {
+			warn!("failed to remove {}: {err}", self.0.display());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn write_atomically_replaces() {
+		let dir = TempDir::new("write");
+		let path = dir.path().join("sub").join("file.json");
+
+		write_atomically(&path, b"old").unwrap();
+		write_atomically(&path, b"new").unwrap();
+		assert_eq!(fs::read(&path).unwrap(), b"new");
+		assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+	}
+
+	#[test]
+	fn write_atomically_failed() {
+		let dir = TempDir::new("write-failed");
+		// a file could not be renamed to a non-empty directory
+		let path = dir.path().join("file.json");
+		fs::create_dir_all(path.join("child")).unwrap();
+
+		assert!(write_atomically(&path, b"content").is_err());
+		assert!(write_new_atomically(&path, b"content").is_err());
+		assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+	}
+
+	#[test]
+	fn write_new_atomically_keeps_existing() {
+		let dir = TempDir::new("write-new");
+		let path = dir.path().join("sub").join("file.json");
+
+		write_new_atomically(&path, b"old").unwrap();
+		let err = write_new_atomically(&path, b"new").unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+		assert_eq!(fs::read(&path).unwrap(), b"old");
+		assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+	}
+
+	#[test]
+	fn write_json_read() {
+		let dir = TempDir::new("write-json");
+		let path = dir.path().join("file.json");
+
+		write_json(&path, &vec![1, 2]).unwrap();
+		assert_eq!(fs::read_to_string(&path).unwrap(), "[\n  1,\n  2\n]");
+		assert_eq!(read_json::<Vec<u32>>(&path).unwrap(), Some(vec![1, 2]));
+	}
+
+	#[test]
+	fn read_json_missing() {
+		let dir = TempDir::new("read-json-missing");
+		let value: Option<Vec<u32>> = read_json(&dir.path().join("file.json")).unwrap();
+		assert_eq!(value, None);
+	}
+
+	#[test]
+	fn read_json_backs_up_unreadable() {
+		let dir = TempDir::new("read-json");
+		let path = dir.path().join("file.json");
+
+		fs::write(&path, "[1, 2]").unwrap();
+		assert_eq!(read_json::<Vec<u32>>(&path).unwrap(), Some(vec![1, 2]));
+
+		for (content, backup) in [
+			(&b"[1, 2"[..], "file.json.bak"),
+			(b"\xff", "file.json.1.bak"),
+			(b"{}", "file.json.2.bak"),
+		] {
+			fs::write(&path, content).unwrap();
+			match read_json::<Vec<u32>>(&path) {
+				Err(Error::Unreadable { backup: path, .. }) => {
+					assert_eq!(path, dir.path().join(backup));
+				}
+				result => panic!("{result:?}"),
+			}
+			assert!(!path.exists());
+			assert_eq!(fs::read(dir.path().join(backup)).unwrap(), content);
+		}
+	}
+}
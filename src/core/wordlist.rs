@@ -0,0 +1,254 @@
+//! Word lists of starred entries.
+
+use crate::history::csv_field;
+use crate::{Result, read_json, write_json};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Name of the list created when there is none.
+pub const DEFAULT_LIST_NAME: &str = "Favorites";
+
+/// A starred entry, which is keyed by dictionary name instead of path so that it
+/// survives re-imports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordListItem {
+	pub dict: String,
+	pub term: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordList {
+	pub name: String,
+	pub items: Vec<WordListItem>,
+}
+
+impl WordList {
+	#[must_use]
+	pub fn new(name: String) -> Self {
+		Self {
+			name,
+			items: Vec::new(),
+		}
+	}
+
+	#[must_use]
+	pub fn contains(&self, item: &WordListItem) -> bool {
+		self.items.contains(item)
+	}
+
+	/// Add `item` if it's not in the list, otherwise remove it.
+	pub fn toggle(&mut self, item: WordListItem) {
+		if let Some(i) = self.items.iter().position(|x| *x == item) {
+			self.items.remove(i);
+		} else {
+			self.items.push(item);
+		}
+	}
+
+	/// Move the item at `from` to `to`, do nothing if any of them is out of range.
+	pub fn move_item(&mut self, from: usize, to: usize) {
+		if from < self.items.len() && to < self.items.len() {
+			let item = self.items.remove(from);
+			self.items.insert(to, item);
+		}
+	}
+
+	#[must_use]
+	pub fn to_csv(&self) -> String {
+		let mut csv = String::from("term,dictionary\n");
+		for item in &self.items {
+			csv.push_str(&format!(
+				"{},{}\n",
+				csv_field(&item.term),
+				csv_field(&item.dict)
+			));
+		}
+
+		csv
+	}
+
+	#[must_use]
+	pub fn to_markdown(&self) -> String {
+		let mut md = format!("# {}\n\n", self.name);
+		for item in &self.items {
+			md.push_str(&format!("- **{}** ({})\n", item.term, item.dict));
+		}
+
+		md
+	}
+}
+
+/// All word lists, which are stored in a JSON file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordLists {
+	pub lists: Vec<WordList>,
+}
+
+impl Default for WordLists {
+	fn default() -> Self {
+		Self {
+			lists: vec![WordList::new(DEFAULT_LIST_NAME.to_string())],
+		}
+	}
+}
+
+impl WordLists {
+	/// Load word lists from `path`, returns the default lists if the file doesn't exist.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if the file could not be read or parsed, see [`read_json`]
+	pub fn load(path: &Path) -> Result<Self> {
+		let mut lists: Self = read_json(path)?.unwrap_or_default();
+		if lists.lists.is_empty() {
+			lists = Self::default();
+		}
+
+		Ok(lists)
+	}
+
+	/// # Errors
+	///
+	/// Will return `Err` if the file could not be written
+	pub fn save(&self, path: &Path) -> Result<()> {
+		write_json(path, self)
+	}
+
+	/// Whether `item` is in any of the lists.
+	#[must_use]
+	pub fn contains(&self, item: &WordListItem) -> bool {
+		self.lists.iter().any(|list| list.contains(item))
+	}
+
+	/// Add a new empty list named `name`, returns its index.
+	///
+	/// If there is already a list named `name`, returns its index instead.
+	pub fn add_list(&mut self, name: String) -> usize {
+		if let Some(i) = self.lists.iter().position(|list| list.name == name) {
+			return i;
+		}
+
+		self.lists.push(WordList::new(name));
+		self.lists.len() - 1
+	}
+
+	/// Update items of the dictionary named `from` after it's renamed to `to`, returns
+	/// whether any item is updated.
+	pub fn rename_dict(&mut self, from: &str, to: &str) -> bool {
+		let mut renamed = false;
+		for item in self.lists.iter_mut().flat_map(|list| &mut list.items) {
+			if item.dict == from {
+				item.dict = to.to_string();
+				renamed = true;
+			}
+		}
+
+		renamed
+	}
+
+	/// Remove the list at `index`, the default list is recreated if no list is left.
+	pub fn remove_list(&mut self, index: usize) {
+		if index < self.lists.len() {
+			self.lists.remove(index);
+		}
+		if self.lists.is_empty() {
+			*self = Self::default();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::TempDir;
+
+	fn item(dict: &str, term: &str) -> WordListItem {
+		WordListItem {
+			dict: dict.to_string(),
+			term: term.to_string(),
+		}
+	}
+
+	#[test]
+	fn toggle_and_move() {
+		let mut list = WordList::new("Verbs".to_string());
+
+		list.toggle(item("English", "run"));
+		list.toggle(item("English", "walk"));
+		list.toggle(item("French", "run"));
+		assert!(list.contains(&item("French", "run")));
+		list.toggle(item("English", "run"));
+		assert!(!list.contains(&item("English", "run")));
+		assert_eq!(list.items, [item("English", "walk"), item("French", "run")]);
+
+		list.move_item(1, 0);
+		assert_eq!(list.items, [item("French", "run"), item("English", "walk")]);
+		list.move_item(0, 2);
+		assert_eq!(list.items, [item("French", "run"), item("English", "walk")]);
+	}
+
+	#[test]
+	fn export() {
+		let mut list = WordList::new("Verbs".to_string());
+		list.toggle(item("English", "run"));
+		list.toggle(item("Say \"hi\"", "a, b"));
+
+		assert_eq!(
+			list.to_csv(),
+			"term,dictionary\nrun,English\n\"a, b\",\"Say \"\"hi\"\"\"\n"
+		);
+		assert_eq!(
+			list.to_markdown(),
+			"# Verbs\n\n- **run** (English)\n- **a, b** (Say \"hi\")\n"
+		);
+	}
+
+	#[test]
+	fn add_rename_remove() {
+		let mut lists = WordLists::default();
+		assert_eq!(lists.lists[0].name, DEFAULT_LIST_NAME);
+
+		assert_eq!(lists.add_list("Verbs".to_string()), 1);
+		assert_eq!(lists.add_list("Verbs".to_string()), 1);
+		lists.lists[1].toggle(item("English", "run"));
+		assert!(lists.contains(&item("English", "run")));
+
+		assert!(lists.rename_dict("English", "Eng"));
+		assert!(!lists.rename_dict("English", "Eng"));
+		assert!(lists.contains(&item("Eng", "run")));
+		assert!(!lists.contains(&item("English", "run")));
+
+		lists.remove_list(5);
+		assert_eq!(lists.lists.len(), 2);
+		lists.remove_list(0);
+		assert_eq!(lists.lists[0].name, "Verbs");
+		// the default list is recreated when the last one is removed
+		lists.remove_list(0);
+		assert_eq!(lists, WordLists::default());
+	}
+
+	#[test]
+	fn load_saved() {
+		let dir = TempDir::new("wordlists");
+		let path = dir.path().join("wordlists.json");
+		assert_eq!(WordLists::load(&path).unwrap(), WordLists::default());
+
+		let mut lists = WordLists::default();
+		lists.lists[0].toggle(item("English", "run"));
+		lists.save(&path).unwrap();
+		assert_eq!(WordLists::load(&path).unwrap(), lists);
+
+		std::fs::write(&path, r#"{"lists": []}"#).unwrap();
+		assert_eq!(WordLists::load(&path).unwrap(), WordLists::default());
+		std::fs::write(&path, "not json").unwrap();
+		assert!(matches!(
+			WordLists::load(&path),
+			Err(crate::Error::Unreadable { .. })
+		));
+		// the unreadable file is kept, so saving doesn't overwrite it
+		lists.save(&path).unwrap();
+		let backup = dir.path().join("wordlists.json.bak");
+		assert_eq!(std::fs::read_to_string(backup).unwrap(), "not json");
+		assert_eq!(WordLists::load(&path).unwrap(), lists);
+	}
+}
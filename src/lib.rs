@@ -1,4 +1,7 @@
+#[cfg(feature = "gui")]
 pub mod app;
+pub mod cli;
 pub mod core;
+pub mod server;
 
 pub use core::*;
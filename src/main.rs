@@ -1,10 +1,57 @@
 // SPDX-License-Identifier: MIT
 
 use std::env;
+use std::process::ExitCode;
 
+use mydict::{DictStore, cli};
+#[cfg(feature = "gui")]
 use mydict::{app, i18n};
 
-fn main() -> cosmic::iced::Result {
+fn main() -> ExitCode {
+	let args: Vec<String> = env::args().skip(1).collect();
+
+	let command = match cli::Command::parse(&args) {
+		Ok(cli::Command::Help) => {
+			println!("{}", cli::USAGE);
+			return ExitCode::SUCCESS;
+		}
+		Ok(command) => command,
+		Err(err) => {
+			eprintln!("mydict: {err}\n\n{}", cli::USAGE);
+			return ExitCode::from(2);
+		}
+	};
+
+	let store = match DictStore::from_env() {
+		Ok(store) => store,
+		Err(err) => {
+			eprintln!("mydict: {err}");
+			return ExitCode::FAILURE;
+		}
+	};
+
+	match command {
+		cli::Command::Gui { term } => run_gui(term.unwrap_or_default(), &store),
+		command => {
+			// Keep stdout clean for scripts, but let the server report what it's doing.
+			let level = if matches!(command, cli::Command::Serve { .. }) {
+				tracing::Level::INFO
+			} else {
+				tracing::Level::WARN
+			};
+			tracing_subscriber::fmt()
+				.with_writer(std::io::stderr)
+				.with_max_level(level)
+				.init();
+
+			command.run(&store)
+		}
+	}
+}
+
+/// Launch the GUI with `term` as the initial search term.
+#[cfg(feature = "gui")]
+fn run_gui(term: String, store: &DictStore) -> ExitCode {
 	tracing_subscriber::fmt::init();
 
 	// Get the system's preferred languages.
@@ -19,13 +66,22 @@ fn main() -> cosmic::iced::Result {
 			.min_width(360.0)
 			.min_height(180.0),
 	);
+	let flags = app::Flags {
+		term,
+		store: store.clone(),
+	};
+
+	match cosmic::app::run::<app::AppModel>(settings, flags) {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(err) => {
+			eprintln!("mydict: {err}");
+			ExitCode::FAILURE
+		}
+	}
+}
 
-	cosmic::app::run::<app::AppModel>(
-		settings,
-		env::args()
-			.collect::<Vec<String>>()
-			.get(1)
-			.cloned()
-			.unwrap_or_default(),
-	)
+#[cfg(not(feature = "gui"))]
+fn run_gui(_term: String, _store: &DictStore) -> ExitCode {
+	eprintln!("mydict: built without the GUI\n\n{}", cli::USAGE);
+	ExitCode::from(2)
 }
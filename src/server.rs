@@ -0,0 +1,659 @@
+// SPDX-License-Identifier: MIT
+
+//! Server of the DICT protocol, see <https://www.rfc-editor.org/rfc/rfc2229>.
+//!
+//! Supported commands are `DEFINE`, `MATCH` (with `exact` and `prefix` strategies),
+//! `SHOW DB`, `SHOW STRAT`, `SHOW INFO`, `CLIENT`, `STATUS`, `HELP` and `QUIT`.
+
+use crate::render::{Format, render_entry};
+use crate::{Dictionary, Folding, IndexCache, LazyDict};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// The well-known port of the DICT protocol.
+pub const DEFAULT_PORT: u16 = 2628;
+
+const STRATEGIES: [(&str, &str); 2] = [
+	("exact", "Match headwords exactly"),
+	("prefix", "Match prefixes"),
+];
+/// Strategy used by `MATCH` when the client asks for the default one, i.e. ".".
+const DEFAULT_STRATEGY: &str = "prefix";
+/// Longest command line accepted, RFC 2229 limits them to 1024 bytes including CRLF.
+const MAX_LINE: u64 = 1024;
+/// Most matches returned by `MATCH`, others are left out.
+const MAX_MATCHES: usize = 1000;
+/// Most clients served at once, further connections are refused.
+const MAX_CONNECTIONS: usize = 64;
+/// Connections are closed if clients send nothing for this long, so idle ones don't
+/// keep the slots of [`MAX_CONNECTIONS`].
+const IDLE_TIMEOUT: Duration = Duration::from_mins(5);
+
+const HELP: &str = "\
+DEFINE database word         -- look up word in database
+MATCH database strategy word -- match word in database using strategy
+SHOW DB                      -- list all accessible databases
+SHOW STRAT                   -- list available matching strategies
+SHOW INFO database           -- provide information about the database
+CLIENT info                  -- identify client to server
+STATUS                       -- display timing information
+HELP                         -- display this help information
+QUIT                         -- terminate connection";
+
+struct Database {
+	/// Name without whitespace, used by clients to refer to the database. Ids of
+	/// dictionaries of the same name are made unique by number suffixes.
+	id: String,
+	description: String,
+	dict: RwLock<LazyDict>,
+}
+
+impl Database {
+	/// Run `f` with the dictionary, loading it first if needed.
+	///
+	/// Returns `None` if the dictionary could not be loaded.
+	fn with_loaded<R>(&self, cache: &IndexCache, f: impl FnOnce(&LazyDict) -> R) -> Option<R> {
+		{
+			let dict = self.dict.read().ok()?;
+			if dict.is_loaded() {
+				return Some(f(&dict));
+			}
+		}
+
+		let mut dict = self.dict.write().ok()?;
+		if !dict.is_loaded() {
+			match Dictionary::load_from_path_cached(&dict.path, Folding::default(), cache) {
+				Ok(dictionary) => dict.load(dictionary),
+				Err(err) => {
+					warn!("failed to load {:?}: {err}", dict.path);
+					return None;
+				}
+			}
+		}
+
+		Some(f(&dict))
+	}
+}
+
+/// A DICT server backed by a collection of dictionaries, which are loaded on first use.
+pub struct Server {
+	databases: Vec<Database>,
+	cache: IndexCache,
+	/// Number of connections accepted so far.
+	connections: AtomicUsize,
+	/// Number of connections being served.
+	active: AtomicUsize,
+	idle_timeout: Duration,
+}
+
+impl Server {
+	#[must_use]
+	pub fn new(dicts: Vec<LazyDict>, cache: IndexCache) -> Self {
+		let mut ids = HashSet::new();
+		let databases = dicts
+			.into_iter()
+			.map(|dict| {
+				let description = dict.name();
+				let name = description.split_whitespace().collect::<Vec<_>>().join("_");
+				let mut id = name.clone();
+				for n in 2.. {
+					if ids.insert(id.clone()) {
+						break;
+					}
+					id = format!("{name}_{n}");
+				}
+				Database {
+					id,
+					description,
+					dict: RwLock::new(dict),
+				}
+			})
+			.collect();
+
+		Self {
+			databases,
+			cache,
+			connections: AtomicUsize::new(0),
+			active: AtomicUsize::new(0),
+			idle_timeout: IDLE_TIMEOUT,
+		}
+	}
+
+	/// Close connections idle for `timeout` instead of [`IDLE_TIMEOUT`].
+	#[must_use]
+	pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+		self.idle_timeout = timeout;
+		self
+	}
+
+	/// Accept connections on `addr` forever, each client is served in its own thread.
+	///
+	/// At most [`MAX_CONNECTIONS`] clients are served at once, others are told to come back
+	/// later.
+	///
+	/// # Errors
+	///
+	/// Will return `Err` if could not listen on `addr`
+	pub fn listen(self, addr: impl ToSocketAddrs) -> io::Result<()> {
+		let listener = TcpListener::bind(addr)?;
+		info!("listening on {}", listener.local_addr()?);
+
+		let server = Arc::new(self);
+		for stream in listener.incoming() {
+			let stream = match stream {
+				Ok(stream) => stream,
+				Err(err) => {
+					warn!("failed to accept connection: {err}");
+					continue;
+				}
+			};
+
+			if server.active.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+				server.active.fetch_sub(1, Ordering::AcqRel);
+				warn!("too many connections, refusing one");
+				let mut stream = stream;
+				_ = stream.write_all(status(420, "Server temporarily unavailable").as_bytes());
+				continue;
+			}
+
+			let server = Arc::clone(&server);
+			thread::spawn(move || {
+				if let Err(err) = server.handle(stream) {
+					warn!("connection error: {err}");
+				}
+				server.active.fetch_sub(1, Ordering::AcqRel);
+			});
+		}
+
+		Ok(())
+	}
+
+	fn handle(&self, stream: TcpStream) -> io::Result<()> {
+		let connection = self.connections.fetch_add(1, Ordering::Relaxed);
+		info!("connection #{connection} from {}", stream.peer_addr()?);
+		stream.set_read_timeout(Some(self.idle_timeout))?;
+
+		let mut reader = BufReader::new(stream.try_clone()?.take(MAX_LINE));
+		let mut writer = BufWriter::new(stream);
+		write!(
+			writer,
+			"220 mydict DICT server <> <{connection}.{}@mydict>\r\n",
+			std::process::id()
+		)?;
+		writer.flush()?;
+
+		let mut line = String::new();
+		loop {
+			line.clear();
+			reader.get_mut().set_limit(MAX_LINE);
+			match reader.read_line(&mut line) {
+				Ok(0) => break,
+				Ok(_) => {}
+				Err(err)
+					if matches!(
+						err.kind(),
+						io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+					) =>
+				{
+					info!("connection #{connection} is idle, closing it");
+					break;
+				}
+				Err(err) => return Err(err),
+			}
+
+			let (response, quit) = if line.ends_with('\n') {
+				self.respond(line.trim_end())
+			} else if reader.get_ref().limit() == 0 {
+				// skip the rest of the line, so that it's not taken as another command
+				reader.get_mut().set_limit(u64::MAX);
+				skip_line(&mut reader)?;
+				(status(500, "Syntax error, line too long"), false)
+			} else {
+				// the client closed the connection without ending the line
+				self.respond(line.trim_end())
+			};
+			writer.write_all(response.as_bytes())?;
+			writer.flush()?;
+			if quit {
+				break;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Returns the response to a command line, and whether to close the connection.
+	#[must_use]
+	pub fn respond(&self, line: &str) -> (String, bool) {
+		let params = split_params(line);
+		let Some(command) = params.first() else {
+			return (status(500, "Syntax error, command not recognized"), false);
+		};
+
+		let response = match (command.to_uppercase().as_str(), &params[1..]) {
+			("DEFINE", [database, word]) => self.define(database, word),
+			("MATCH", [database, strategy, word]) => self.match_words(database, strategy, word),
+			("SHOW", [what]) => match what.to_uppercase().as_str() {
+				"DB" | "DATABASES" => self.show_databases(),
+				"STRAT" | "STRATEGIES" => show_strategies(),
+				_ => status(501, "Syntax error, illegal parameters"),
+			},
+			("SHOW", [what, database]) if what.eq_ignore_ascii_case("INFO") => {
+				self.show_info(database)
+			}
+			("CLIENT", [_, ..]) => status(250, "ok"),
+			("STATUS", []) => status(210, &format!("status [databases {}]", self.databases.len())),
+			("HELP", []) => {
+				let mut response = status(113, "help text follows");
+				push_text(&mut response, HELP);
+				response.push_str(&status(250, "ok"));
+				response
+			}
+			("QUIT", []) => return (status(221, "bye"), true),
+			("DEFINE" | "MATCH" | "SHOW" | "CLIENT" | "STATUS" | "HELP" | "QUIT", _) => {
+				status(501, "Syntax error, illegal parameters")
+			}
+			_ => status(500, "Syntax error, command not recognized"),
+		};
+
+		(response, false)
+	}
+
+	/// Returns databases selected by `database`, `None` if there is no such database.
+	///
+	/// Both "*" and "!" select all databases, the latter means to stop at the first one
+	/// with results.
+	fn select(&self, database: &str) -> Option<Vec<&Database>> {
+		if database == "*" || database == "!" {
+			return Some(self.databases.iter().collect());
+		}
+
+		self.databases
+			.iter()
+			.find(|db| db.id == database)
+			.map(|db| vec![db])
+	}
+
+	fn define(&self, database: &str, word: &str) -> String {
+		let Some(databases) = self.select(database) else {
+			return invalid_database();
+		};
+
+		let mut definitions = Vec::new();
+		for db in databases {
+			let definition = db
+				.with_loaded(&self.cache, |dict| {
+					let entry = dict.get(word).ok().flatten()?;
+					Some((entry.term.clone(), render_entry(entry, Format::Text, None)))
+				})
+				.flatten();
+			if let Some(definition) = definition {
+				definitions.push((db, definition));
+				if database == "!" {
+					break;
+				}
+			}
+		}
+
+		if definitions.is_empty() {
+			return status(552, "No match");
+		}
+
+		let mut response = status(150, &format!("{} definitions retrieved", definitions.len()));
+		for (db, (term, text)) in definitions {
+			response.push_str(&status(
+				151,
+				&format!("{} {} {}", quote(&term), db.id, quote(&db.description)),
+			));
+			push_text(&mut response, &text);
+		}
+		response.push_str(&status(250, "ok"));
+
+		response
+	}
+
+	fn match_words(&self, database: &str, strategy: &str, word: &str) -> String {
+		let strategy = if strategy == "." {
+			DEFAULT_STRATEGY
+		} else {
+			strategy
+		};
+		if !STRATEGIES.iter().any(|(name, _)| *name == strategy) {
+			return status(
+				551,
+				"Invalid strategy, use \"SHOW STRAT\" for a list of strategies",
+			);
+		}
+		let Some(databases) = self.select(database) else {
+			return invalid_database();
+		};
+
+		let mut matches = Vec::new();
+		for db in databases {
+			let words = db
+				.with_loaded(&self.cache, |dict| match strategy {
+					"exact" => dict
+						.get(word)
+						.ok()
+						.flatten()
+						.map(|entry| vec![entry.term.clone()])
+						.unwrap_or_default(),
+					_ => dict.search(word, 0, MAX_MATCHES).unwrap_or_default(),
+				})
+				.unwrap_or_default();
+			if !words.is_empty() {
+				matches.extend(words.into_iter().map(|word| (db, word)));
+				if database == "!" {
+					break;
+				}
+			}
+		}
+
+		if matches.is_empty() {
+			return status(552, "No match");
+		}
+		matches.truncate(MAX_MATCHES);
+
+		let mut response = status(152, &format!("{} matches found", matches.len()));
+		let mut text = String::new();
+		for (db, word) in matches {
+			_ = writeln!(text, "{} {}", db.id, quote(&word));
+		}
+		push_text(&mut response, &text);
+		response.push_str(&status(250, "ok"));
+
+		response
+	}
+
+	fn show_databases(&self) -> String {
+		if self.databases.is_empty() {
+			return status(554, "No databases present");
+		}
+
+		let mut response = status(110, &format!("{} databases present", self.databases.len()));
+		let mut text = String::new();
+		for db in &self.databases {
+			_ = writeln!(text, "{} {}", db.id, quote(&db.description));
+		}
+		push_text(&mut response, &text);
+		response.push_str(&status(250, "ok"));
+
+		response
+	}
+
+	fn show_info(&self, database: &str) -> String {
+		let Some(db) = self.databases.iter().find(|db| db.id == database) else {
+			return invalid_database();
+		};
+		let path = match db.dict.read() {
+			Ok(dict) => dict.path.display().to_string(),
+			Err(_) => String::new(),
+		};
+
+		let mut response = status(112, "database information follows");
+		push_text(&mut response, &format!("{}\nFile: {path}", db.description));
+		response.push_str(&status(250, "ok"));
+
+		response
+	}
+}
+
+fn show_strategies() -> String {
+	let mut response = status(111, &format!("{} strategies available", STRATEGIES.len()));
+	let mut text = String::new();
+	for (name, description) in STRATEGIES {
+		_ = writeln!(text, "{name} {}", quote(description));
+	}
+	push_text(&mut response, &text);
+	response.push_str(&status(250, "ok"));
+
+	response
+}
+
+fn invalid_database() -> String {
+	status(
+		550,
+		"Invalid database, use \"SHOW DB\" for list of databases",
+	)
+}
+
+fn status(code: u16, text: &str) -> String {
+	format!("{code} {text}\r\n")
+}
+
+/// Append `text` as a textual response, which is terminated by a line of a single ".".
+fn push_text(response: &mut String, text: &str) {
+	for line in text.lines() {
+		// lines starting with "." are escaped by doubling it
+		if line.starts_with('.') {
+			response.push('.');
+		}
+		response.push_str(line);
+		response.push_str("\r\n");
+	}
+	response.push_str(".\r\n");
+}
+
+/// Discard input up to and including the next newline, without buffering all of it.
+fn skip_line(reader: &mut impl BufRead) -> io::Result<()> {
+	loop {
+		let buf = reader.fill_buf()?;
+		if buf.is_empty() {
+			return Ok(());
+		}
+		if let Some(i) = buf.iter().position(|&b| b == b'\n') {
+			reader.consume(i + 1);
+			return Ok(());
+		}
+		let len = buf.len();
+		reader.consume(len);
+	}
+}
+
+fn quote(s: &str) -> String {
+	format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Split a command line into parameters, which may be quoted by `"` or `'`.
+fn split_params(line: &str) -> Vec<String> {
+	let mut params = Vec::new();
+	let mut current: Option<String> = None;
+	let mut quote = None;
+	let mut chars = line.chars();
+
+	while let Some(c) = chars.next() {
+		match (quote, c) {
+			(_, '\\') => {
+				if let Some(escaped) = chars.next() {
+					current.get_or_insert_default().push(escaped);
+				}
+			}
+			(None, '"' | '\'') => {
+				quote = Some(c);
+				current.get_or_insert_default();
+			}
+			(Some(q), c) if c == q => quote = None,
+			(None, c) if c.is_whitespace() => {
+				if let Some(param) = current.take() {
+					params.push(param);
+				}
+			}
+			(_, c) => current.get_or_insert_default().push(c),
+		}
+	}
+	if let Some(param) = current {
+		params.push(param);
+	}
+
+	params
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::Shutdown;
+	use std::path::PathBuf;
+
+	fn entry(term: &str, definition: &str) -> odict::Entry {
+		let sense = odict::Sense {
+			definitions: vec![odict::DefinitionType::Definition(odict::Definition {
+				value: definition.to_string(),
+				..Default::default()
+			})],
+			..Default::default()
+		};
+		odict::Entry {
+			term: term.to_string(),
+			etymologies: vec![odict::Etymology {
+				senses: std::iter::once(sense).collect(),
+				..Default::default()
+			}],
+			..Default::default()
+		}
+	}
+
+	fn server(terms: &[&str]) -> Server {
+		let mut odict = odict::Dictionary {
+			name: Some("Test Dict".to_string()),
+			..Default::default()
+		};
+		for term in terms {
+			odict
+				.entries
+				.insert(entry(term, &format!("definition of {term}")));
+		}
+		let mut dict = LazyDict::new(PathBuf::from("test.odict"));
+		dict.load(Dictionary::new(odict));
+
+		Server::new(vec![dict], IndexCache::new(PathBuf::from("/nonexistent")))
+	}
+
+	#[test]
+	fn define() {
+		let server = server(&["run", "runner", "walk"]);
+
+		let (response, quit) = server.respond("DEFINE Test_Dict run");
+		assert!(!quit);
+		assert!(response.starts_with("150 1 definitions retrieved\r\n"));
+		assert!(response.contains("151 \"run\" Test_Dict \"Test Dict\"\r\n"));
+		assert!(response.contains("definition of run"));
+		assert!(response.ends_with(".\r\n250 ok\r\n"));
+
+		assert_eq!(server.respond("DEFINE * swim").0, "552 No match\r\n");
+		assert!(server.respond("DEFINE Other run").0.starts_with("550 "));
+		assert!(server.respond("DEFINE run").0.starts_with("501 "));
+	}
+
+	#[test]
+	fn match_words() {
+		let server = server(&["run", "runner", "walk"]);
+
+		let (response, _) = server.respond("MATCH Test_Dict prefix ru");
+		assert!(response.starts_with("152 2 matches found\r\n"));
+		assert!(response.contains("Test_Dict \"run\"\r\n"));
+		assert!(response.contains("Test_Dict \"runner\"\r\n"));
+		assert!(response.ends_with(".\r\n250 ok\r\n"));
+
+		let (response, _) = server.respond("MATCH * exact walk");
+		assert!(response.starts_with("152 1 matches found\r\n"));
+		assert_eq!(server.respond("MATCH * . swim").0, "552 No match\r\n");
+		assert!(server.respond("MATCH * soundex run").0.starts_with("551 "));
+	}
+
+	#[test]
+	fn match_too_many() {
+		let terms: Vec<_> = (0..=MAX_MATCHES).map(|i| format!("word{i}")).collect();
+		let server = server(&terms.iter().map(String::as_str).collect::<Vec<_>>());
+
+		let (response, _) = server.respond("MATCH * prefix word");
+		assert!(response.starts_with(&format!("152 {MAX_MATCHES} matches found\r\n")));
+		assert!(response.contains("Test_Dict \"word0\"\r\n"));
+		// the last one in order
+		assert!(!response.contains("\"word999\""));
+		assert!(response.ends_with(".\r\n250 ok\r\n"));
+	}
+
+	#[test]
+	fn unique_ids() {
+		let dicts = ["Test Dict", "Test  Dict", "Test_Dict_2", "Other"]
+			.into_iter()
+			.map(|name| {
+				let odict = odict::Dictionary {
+					name: Some(name.to_string()),
+					..Default::default()
+				};
+				let mut dict = LazyDict::new(PathBuf::from(format!("{name}.odict")));
+				dict.load(Dictionary::new(odict));
+				dict
+			})
+			.collect();
+		let server = Server::new(dicts, IndexCache::new(PathBuf::from("/nonexistent")));
+
+		let ids: Vec<_> = server.databases.iter().map(|db| db.id.as_str()).collect();
+		assert_eq!(ids, ["Test_Dict", "Test_Dict_2", "Test_Dict_2_2", "Other"]);
+		let (response, _) = server.respond("SHOW INFO Test_Dict_2");
+		assert!(response.contains("\r\nTest  Dict\r\n"));
+	}
+
+	#[test]
+	fn idle_timeout() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		let handle = thread::spawn(move || {
+			let (stream, _) = listener.accept().unwrap();
+			server(&["run"])
+				.with_idle_timeout(Duration::from_millis(50))
+				.handle(stream)
+				.unwrap();
+		});
+
+		// the connection is closed by the server without any command
+		let mut client = TcpStream::connect(addr).unwrap();
+		let mut response = String::new();
+		client.read_to_string(&mut response).unwrap();
+		handle.join().unwrap();
+
+		assert!(response.starts_with("220 "));
+	}
+
+	#[test]
+	fn quit() {
+		let server = server(&[]);
+
+		assert_eq!(server.respond("QUIT"), ("221 bye\r\n".to_string(), true));
+		assert_eq!(server.respond("quit"), ("221 bye\r\n".to_string(), true));
+		assert!(!server.respond("QUIT now").1);
+	}
+
+	#[test]
+	fn line_too_long() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = listener.local_addr().unwrap();
+		let handle = thread::spawn(move || {
+			let (stream, _) = listener.accept().unwrap();
+			server(&["run"]).handle(stream).unwrap();
+		});
+
+		let mut client = TcpStream::connect(addr).unwrap();
+		let long = "x".repeat(usize::try_from(MAX_LINE).unwrap() * 3);
+		write!(client, "DEFINE * {long}\r\nDEFINE * run\r\nQUIT\r\n").unwrap();
+		client.shutdown(Shutdown::Write).unwrap();
+		let mut response = String::new();
+		client.read_to_string(&mut response).unwrap();
+		handle.join().unwrap();
+
+		let codes: Vec<_> = response
+			.lines()
+			.filter_map(|line| line.get(..4))
+			.filter(|code| code.ends_with(' ') && code[..3].bytes().all(|b| b.is_ascii_digit()))
+			.collect();
+		assert_eq!(codes, ["220 ", "500 ", "150 ", "151 ", "250 ", "221 "]);
+	}
+}